@@ -1,41 +1,472 @@
-use darling::{ast, util, FromDeriveInput, FromField, FromVariant};
+use darling::{ast, util, util::SpannedValue, FromDeriveInput, FromField, FromMeta, FromVariant};
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::TokenTree;
+use quote::{format_ident, quote};
+use std::str::FromStr;
 use strum::EnumString;
-use syn::{parse_macro_input, DeriveInput, Ident, Type};
+use syn::{parse_macro_input, DeriveInput, GenericArgument, Ident, PathArguments, Type};
 
 // cargo expand --test test_simple
 // RUSTFLAGS="-Z macro-backtrace" cargo test
 // da eseguire con rust nightly
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(fixed_width), supports(struct_named))]
+#[darling(
+    attributes(fixed_width),
+    supports(struct_named, struct_tuple, struct_newtype)
+)]
 struct FixedWidthFields {
     ident: Ident,
+    generics: syn::Generics,
     data: ast::Data<util::Ignored, FixedWidthField>,
+    // expected length of the whole record, checked at compile time against the sum of the field sizes
+    record_len: Option<SpannedValue<usize>>,
+    // char used to fill the gaps between fields declared by position; without it a gap is an error
+    gap_filler: Option<SpannedValue<char>>,
+    // literal segments written at a fixed position, with no Rust field behind them
+    #[darling(multiple, rename = "filler")]
+    fillers: Vec<SpannedValue<Filler>>,
+    #[darling(multiple, rename = "constant")]
+    constants: Vec<SpannedValue<Constant>>,
+    // defaults for every field of the struct
+    #[darling(flatten)]
+    defaults: FieldDefaults,
+    // defaults for the fields of a given type, e.g. #[fixed_width(number(pad = "0", pad_left = true))]
+    text: Option<FieldDefaults>,
+    number: Option<FieldDefaults>,
+    date: Option<FieldDefaults>,
+    #[darling(rename = "bool")]
+    boolean: Option<FieldDefaults>,
+    // opt-in: text, dates and bools left-aligned and space-filled, numbers right-aligned and zero-filled
+    #[darling(default)]
+    align_by_type: bool,
+    // runs validate() before every record is written
+    #[darling(default)]
+    validate: bool,
+    // also derives FromFixedWidth, which reads the record back with the same layout
+    #[darling(default)]
+    parse: bool,
 }
 
-//#[darling(default)]
-//skip: bool,
+impl FixedWidthFields {
+    fn type_defaults(&self, field_type: Option<FieldType>) -> Option<&FieldDefaults> {
+        match field_type? {
+            FieldType::Text => self.text.as_ref(),
+            FieldType::Number => self.number.as_ref(),
+            FieldType::Date => self.date.as_ref(),
+            FieldType::Bool => self.boolean.as_ref(),
+        }
+    }
+
+    fn standard_defaults(&self, field_type: Option<FieldType>) -> Option<FieldDefaults> {
+        if !self.align_by_type {
+            return None;
+        }
+
+        let (pad, pad_left) = match field_type? {
+            FieldType::Text | FieldType::Date | FieldType::Bool => (' ', false),
+            FieldType::Number => ('0', true),
+        };
+        Some(FieldDefaults {
+            pad: Some(pad),
+            pad_left: Some(pad_left),
+            ..Default::default()
+        })
+    }
+}
+
+// field options that can also be set at struct level, for all fields or for a type of field.
+// The most specific one wins: field, then type of field, then struct, then align_by_type
+#[derive(Debug, Default, Clone, FromMeta)]
+struct FieldDefaults {
+    pad: Option<char>,
+    pad_left: Option<bool>,
+    decimals: Option<usize>,
+    date_format: Option<String>,
+    time_format: Option<String>,
+    date_time_format: Option<String>,
+    // how a bool is written, e.g. #[fixed_width(true = "S", false = "N")]
+    true_value: Option<String>,
+    false_value: Option<String>,
+    // how a None is written, e.g. null = "N/A" or null_fill = "0"
+    null: Option<String>,
+    null_fill: Option<char>,
+    // offset datetimes are converted to before formatting, e.g. offset = "+01:00" or offset = "Z"
+    offset: Option<String>,
+    // legacy date encodings, e.g. date_encoding = "short_ordinal" for YYDDD, with the pivot of two-digit years
+    date_encoding: Option<String>,
+    pivot: Option<i32>,
+    epoch: Option<String>,
+    // e.g. duration_format = "hhmmss", or a total such as "minutes" scaled by decimals
+    duration_format: Option<String>,
+    // "hyphenated" (the default) or "simple"
+    uuid_format: Option<String>,
+    // text transforms applied before padding, e.g. case = "upper", trim, collapse_whitespace, transliterate
+    case: Option<String>,
+    trim: Option<bool>,
+    collapse_whitespace: Option<bool>,
+    transliterate: Option<bool>,
+    // chars allowed in the value: numeric, alpha, alnum, ascii_printable or a set such as "[A-Z0-9 ]"
+    charset: Option<String>,
+}
+
+impl FieldDefaults {
+    fn or(self, other: Option<&FieldDefaults>) -> FieldDefaults {
+        let Some(other) = other else {
+            return self;
+        };
+        FieldDefaults {
+            pad: self.pad.or(other.pad),
+            pad_left: self.pad_left.or(other.pad_left),
+            decimals: self.decimals.or(other.decimals),
+            date_format: self.date_format.or_else(|| other.date_format.clone()),
+            time_format: self.time_format.or_else(|| other.time_format.clone()),
+            date_time_format: self
+                .date_time_format
+                .or_else(|| other.date_time_format.clone()),
+            true_value: self.true_value.or_else(|| other.true_value.clone()),
+            false_value: self.false_value.or_else(|| other.false_value.clone()),
+            null: self.null.or_else(|| other.null.clone()),
+            null_fill: self.null_fill.or(other.null_fill),
+            offset: self.offset.or_else(|| other.offset.clone()),
+            date_encoding: self.date_encoding.or_else(|| other.date_encoding.clone()),
+            pivot: self.pivot.or(other.pivot),
+            epoch: self.epoch.or_else(|| other.epoch.clone()),
+            duration_format: self
+                .duration_format
+                .or_else(|| other.duration_format.clone()),
+            uuid_format: self.uuid_format.or_else(|| other.uuid_format.clone()),
+            case: self.case.or_else(|| other.case.clone()),
+            trim: self.trim.or(other.trim),
+            collapse_whitespace: self.collapse_whitespace.or(other.collapse_whitespace),
+            transliterate: self.transliterate.or(other.transliterate),
+            charset: self.charset.or_else(|| other.charset.clone()),
+        }
+    }
+
+    fn pad(&self) -> char {
+        self.pad.unwrap_or_else(pad_default)
+    }
+
+    fn pad_left(&self) -> bool {
+        self.pad_left.unwrap_or_else(pad_left_default)
+    }
+
+    fn decimals(&self) -> usize {
+        self.decimals.unwrap_or_else(decimals_default)
+    }
+
+    fn date_format(&self) -> String {
+        self.date_format.clone().unwrap_or_else(date_format_default)
+    }
+
+    fn time_format(&self) -> String {
+        self.time_format.clone().unwrap_or_else(time_format_default)
+    }
+
+    fn date_time_format(&self) -> String {
+        self.date_time_format
+            .clone()
+            .unwrap_or_else(date_time_format_default)
+    }
+
+    fn true_value(&self) -> String {
+        self.true_value.clone().unwrap_or_else(true_value_default)
+    }
+
+    fn false_value(&self) -> String {
+        self.false_value.clone().unwrap_or_else(false_value_default)
+    }
+}
+
+// literals are declared positionally, e.g. filler(120, 199) or constant(1, "A"): named arguments repeated
+// across several fillers would trip clippy::duplicated_attributes in the user's crate
+
+// #[fixed_width(filler(120, 199))] or #[fixed_width(filler(120, 199, "0"))], start and end columns are inclusive
+#[derive(Debug)]
+struct Filler {
+    start: usize,
+    end: usize,
+    pad: char,
+}
+
+impl FromMeta for Filler {
+    fn from_list(items: &[ast::NestedMeta]) -> darling::Result<Self> {
+        match items {
+            [start, end] => Ok(Filler {
+                start: usize::from_nested_meta(start)?,
+                end: usize::from_nested_meta(end)?,
+                pad: pad_default(),
+            }),
+            [start, end, pad] => Ok(Filler {
+                start: usize::from_nested_meta(start)?,
+                end: usize::from_nested_meta(end)?,
+                pad: char::from_nested_meta(pad)?,
+            }),
+            _ => Err(darling::Error::custom(
+                "expected filler(start, end) or filler(start, end, \"pad\")",
+            )),
+        }
+    }
+}
+
+// #[fixed_width(constant(1, "A"))]
+#[derive(Debug)]
+struct Constant {
+    start: usize,
+    value: String,
+}
+
+impl FromMeta for Constant {
+    fn from_list(items: &[ast::NestedMeta]) -> darling::Result<Self> {
+        match items {
+            [start, value] => Ok(Constant {
+                start: usize::from_nested_meta(start)?,
+                value: String::from_nested_meta(value)?,
+            }),
+            _ => Err(darling::Error::custom(
+                "expected constant(start, \"value\")",
+            )),
+        }
+    }
+}
+
+// min = 0, min = -10, min = 0.01 or min = "0.01", kept as the text of the number
+#[derive(Debug)]
+struct Bound(String);
+
+impl FromMeta for Bound {
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        let bound = match expr {
+            syn::Expr::Lit(lit) => Self::from_value(&lit.lit)?.0,
+            syn::Expr::Unary(syn::ExprUnary {
+                op: syn::UnOp::Neg(_),
+                expr,
+                ..
+            }) => format!("-{}", Self::from_expr(expr)?.0),
+            _ => return Err(darling::Error::unexpected_expr_type(expr)),
+        };
+        Ok(Bound(bound))
+    }
+
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        let bound = match value {
+            syn::Lit::Int(int) => int.base10_digits().to_string(),
+            syn::Lit::Float(float) => float.base10_digits().to_string(),
+            syn::Lit::Str(str) => str.value(),
+            _ => return Err(darling::Error::unexpected_lit_type(value)),
+        };
+        // parsed as the runtime does, so that e.g. "inf" is refused here
+        match bigdecimal::BigDecimal::from_str(&bound) {
+            Ok(_) => Ok(Bound(bound)),
+            Err(_) => Err(darling::Error::custom(format!("{} is not a number", bound))),
+        }
+    }
+}
+
+// one_of("A", "B") or one_of(1, 2), compared with the written text
+#[derive(Debug)]
+struct OneOf(Vec<String>);
+
+impl FromMeta for OneOf {
+    fn from_list(items: &[ast::NestedMeta]) -> darling::Result<Self> {
+        let values =
+            items
+                .iter()
+                .map(|item| match item {
+                    ast::NestedMeta::Lit(syn::Lit::Str(str)) => Ok(str.value()),
+                    ast::NestedMeta::Lit(syn::Lit::Int(int)) => Ok(int.base10_digits().to_string()),
+                    ast::NestedMeta::Lit(syn::Lit::Char(char)) => Ok(char.value().to_string()),
+                    _ => Err(darling::Error::custom("expected one_of(\"A\", \"B\", ...)")
+                        .with_span(item)),
+                })
+                .collect::<darling::Result<Vec<_>>>()?;
+        match values.is_empty() {
+            true => Err(darling::Error::too_few_items(1)),
+            false => Ok(OneOf(values)),
+        }
+    }
+}
+
+// a filler or a constant, already resolved to the bytes to write
+struct Literal {
+    start: usize,
+    bytes: Vec<u8>,
+    span: proc_macro2::Span,
+}
+
+impl Literal {
+    fn from_filler(filler: &SpannedValue<Filler>) -> darling::Result<Literal> {
+        if filler.end < filler.start {
+            return Err(darling::Error::custom(format!(
+                "filler end {} is before start {}",
+                filler.end, filler.start
+            ))
+            .with_span(&filler.span()));
+        }
+        // a char past ASCII is more than one byte, the filler would not fill its columns
+        if !filler.pad.is_ascii() {
+            return Err(darling::Error::custom(format!(
+                "filler pad '{}' is not an ASCII char",
+                filler.pad
+            ))
+            .with_span(&filler.span()));
+        }
+        let size = filler.end - filler.start + 1;
+
+        Ok(Literal {
+            start: filler.start,
+            bytes: vec![filler.pad as u8; size],
+            span: filler.span(),
+        })
+    }
+
+    fn from_constant(constant: &SpannedValue<Constant>) -> Literal {
+        Literal {
+            start: constant.start,
+            bytes: constant.value.as_bytes().to_vec(),
+            span: constant.span(),
+        }
+    }
+
+    fn error<T: std::fmt::Display>(&self, msg: T) -> darling::Error {
+        darling::Error::custom(format!("literal at column {} {}", self.start, msg))
+            .with_span(&self.span)
+    }
+}
+
+// keeps track of the columns already taken while the record is laid out
+struct Layout {
+    record_len: usize,
+    // RECORD_LEN of the nested records placed without a size, only known to the compiler
+    nested_lens: Vec<proc_macro2::TokenStream>,
+    // set once a field of unknown length is placed, the columns after it can not be checked
+    open_ended: Option<&'static str>,
+    gap_filler: Option<char>,
+    segments: Vec<proc_macro2::TokenStream>,
+    // the same segments, read back
+    reads: Vec<proc_macro2::TokenStream>,
+}
+
+impl Layout {
+    /// Moves the cursor to the 1-based column `start`, filling any gap, and reserves `size` columns.
+    /// Without `start` the columns right after the previous field are taken.
+    fn place(&mut self, start: Option<usize>, size: usize) -> Result<(), String> {
+        let Some(start) = start else {
+            self.record_len += size;
+            return Ok(());
+        };
+        if let Some(after) = self.open_ended {
+            return Err(format!("starts at column {} after {}", start, after));
+        }
+        if start == 0 {
+            return Err("start is a 1-based column and can not be 0".to_string());
+        }
+        if start <= self.record_len {
+            return Err(format!(
+                "starts at column {} but the previous field ends at column {}",
+                start, self.record_len
+            ));
+        }
+        if start > self.record_len + 1 {
+            let gap = start - self.record_len - 1;
+            let Some(gap_filler) = self.gap_filler else {
+                return Err(format!(
+                    "leaves a gap at columns {}-{}, declare a gap_filler on the struct to fill it",
+                    self.record_len + 1,
+                    start - 1
+                ));
+            };
+            let gap_filler = gap_filler as u8;
+            self.segments.push(quote! {
+                res.resize(res.len() + #gap, #gap_filler);
+            });
+            self.reads.push(quote! {
+                fixed_width::read_bytes(bytes, &mut pos, #gap)?;
+            });
+            self.record_len += gap;
+        }
+
+        self.record_len += size;
+        Ok(())
+    }
+
+    fn place_literal(&mut self, literal: &Literal) -> Result<(), String> {
+        self.place(Some(literal.start), literal.bytes.len())?;
+        let bytes = proc_macro2::Literal::byte_string(&literal.bytes);
+        self.segments.push(quote! {
+            res.extend_from_slice(#bytes);
+        });
+        self.reads.push(quote! {
+            fixed_width::read_literal(bytes, &mut pos, #bytes)?;
+        });
+        Ok(())
+    }
+
+    fn place_nested(
+        &mut self,
+        start: Option<usize>,
+        len: proc_macro2::TokenStream,
+    ) -> Result<(), String> {
+        self.place(start, 0)?;
+        self.nested_lens.push(len);
+        self.open_ended
+            .get_or_insert("a nested record without size, give the nested field a size");
+        Ok(())
+    }
+
+    // a depends_on group, written as it is and not counted in RECORD_LEN
+    fn place_variable(&mut self, start: Option<usize>) -> Result<(), String> {
+        self.place(start, 0)?;
+        self.open_ended = Some("a depends_on group, whose length varies");
+        Ok(())
+    }
+
+    fn record_len(&self) -> proc_macro2::TokenStream {
+        let record_len = self.record_len;
+        let nested_lens = &self.nested_lens;
+        quote!(#record_len #(+ #nested_lens)*)
+    }
+}
 
 #[derive(Debug, FromField)]
 #[darling(attributes(fixed_width))]
 struct FixedWidthField {
     ident: Option<Ident>,
     ty: Type,
-    size: usize,
-    #[darling(default = "pad_default")]
-    pad: char,
-    #[darling(default = "pad_left_default")]
-    pad_left: bool,
-    #[darling(default = "decimals_default")]
-    decimals: usize,
-    #[darling(default = "date_format_default")]
-    date_format: String,
-    #[darling(default = "time_format_default")]
-    time_format: String,
-    #[darling(default = "date_time_format_default")]
-    date_time_format: String,
+    // in-memory only field, not written to the record
+    #[darling(default)]
+    skip: bool,
+    // method (fn(&self) -> impl AnyValueTrait) whose result is written instead of the field value,
+    // typically on a `()` field so that nothing is stored
+    compute: Option<syn::Path>,
+    // another FixedWidth record (or an Option of one) written inline, size defaults to its RECORD_LEN
+    #[darling(default)]
+    nested: bool,
+    // repeating group of a Vec<T> or [T; N] field: exactly this many records, the missing ones blank
+    occurs: Option<usize>,
+    // variable group of a Vec<T> field, whose length is written in the named field (OCCURS DEPENDING ON)
+    depends_on: Option<String>,
+    size: Option<usize>,
+    // 1-based, inclusive columns as written in the specs (alternative to size)
+    start: Option<usize>,
+    end: Option<usize>,
+    #[darling(flatten)]
+    options: FieldDefaults,
+    // validation rules, checked by the generated validate(), e.g. min = 0.01, pattern = "^\\d{5}$" or one_of("A", "B")
+    min: Option<Bound>,
+    max: Option<Bound>,
+    pattern: Option<String>,
+    #[darling(default)]
+    required: bool,
+    one_of: Option<OneOf>,
+    // custom serialization: fn(&T, &FieldConfig) -> Result<Vec<u8>, FixedWidthError>,
+    // or a module with such a function named serialize
+    serialize_with: Option<syn::Path>,
+    with: Option<syn::Path>,
+    // the reverse when the struct is parsed: fn(&str, &FieldConfig) -> Result<T, FixedWidthError>,
+    // given the unpadded text, or the function named deserialize of the with module
+    deserialize_with: Option<syn::Path>,
 }
 
 fn pad_default() -> char {
@@ -56,210 +487,1385 @@ fn time_format_default() -> String {
 fn date_time_format_default() -> String {
     "[year][month][day] [hour padding:none][minute][second]".into()
 }
+// names of fixed_width::model::date_encoding::DateEncoding
+#[derive(Debug, Clone, Copy, EnumString)]
+#[strum(serialize_all = "snake_case")]
+enum DateEncoding {
+    Format,
+    Ordinal,
+    ShortOrdinal,
+    ShortYear,
+    EpochDays,
+}
+
+// names of fixed_width::model::duration_format::DurationFormat
+#[derive(Debug, Clone, Copy, EnumString)]
+#[strum(serialize_all = "lowercase")]
+enum DurationFormat {
+    Seconds,
+    Milliseconds,
+    Minutes,
+    Hours,
+    HhMmSs,
+    HhMm,
+}
+
+// names of fixed_width::model::text_transform::TextCase
+#[derive(Debug, Clone, Copy, EnumString)]
+#[strum(serialize_all = "lowercase")]
+enum TextCase {
+    Upper,
+    Lower,
+}
+
+// fixed_width::model::charset::Charset, a set such as "[A-Z0-9 ]" is turned into its ranges of chars;
+// a '\\' takes the char after it literally, e.g. "[0-9\\-]"
+fn parse_charset(charset: &str) -> Option<proc_macro2::TokenStream> {
+    let variant = match charset {
+        "numeric" => quote!(Numeric),
+        "alpha" => quote!(Alpha),
+        "alnum" => quote!(Alnum),
+        "ascii_printable" => quote!(AsciiPrintable),
+        set => {
+            let set = set.strip_prefix('[')?.strip_suffix(']')?;
+            let mut chars = set.chars().peekable();
+            let mut ranges = Vec::new();
+            while let Some(c) = chars.next() {
+                let from = match c {
+                    '\\' => chars.next()?,
+                    c => c,
+                };
+                let to = match chars.next_if_eq(&'-') {
+                    Some(_) => match chars.next()? {
+                        '\\' => chars.next()?,
+                        to => to,
+                    },
+                    None => from,
+                };
+                if to < from {
+                    return None;
+                }
+                ranges.push(quote!((#from, #to)));
+            }
+            if ranges.is_empty() {
+                return None;
+            }
+            quote!(Set(vec![#(#ranges),*]))
+        }
+    };
+    Some(quote!(fixed_width::model::charset::Charset::#variant))
+}
+
+// "Z", "UTC", "+01:00", "-0530" or "+02", in seconds east of UTC
+fn parse_offset(offset: &str) -> Option<i32> {
+    if offset == "Z" || offset == "UTC" {
+        return Some(0);
+    }
+    let sign = match offset.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = offset[1..].replace(':', "");
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+// "1900-01-01", as a julian day
+fn parse_epoch(epoch: &str) -> Option<i32> {
+    let mut parts = epoch.splitn(3, '-');
+    let mut part = || -> Option<&str> {
+        parts
+            .next()
+            .filter(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+    };
+    let (year, month, day) = (part()?, part()?, part()?);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let month = time::Month::try_from(month.parse::<u8>().ok()?).ok()?;
+    time::Date::from_calendar_date(year.parse().ok()?, month, day.parse().ok()?)
+        .ok()
+        .map(|date| date.to_julian_day())
+}
+fn true_value_default() -> String {
+    "1".into()
+}
+fn false_value_default() -> String {
+    "0".into()
+}
 
 impl FixedWidthField {
-    fn field_name(&self) -> String {
+    // `self.name` for named fields, `self.0` for tuple structs
+    fn member(&self, index: usize) -> syn::Member {
+        match self.ident() {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(index.into()),
+        }
+    }
+
+    fn field_name(&self, index: usize) -> String {
         self.ident()
-            .and_then(|i| Some(i.to_string()))
-            .unwrap_or(String::new())
+            .map(|i| i.to_string())
+            .unwrap_or(index.to_string())
     }
 
-    /*fn field_type(&self) -> FieldType {
-        if let Type::Path(path) = self.ty() {
-            let field_type = &path.path.segments.first().unwrap().ident;
-            let field_type_enum = FieldType::from_str(field_type.to_string().as_str()).expect(
-                format!("Unable to parse {} into FieldType", field_type.to_string()).as_str(),
-            );
-            field_type_enum
-        } else {
-            panic!("Unexpected type: {:?}", self.ty());
-        }
-    }*/
+    fn field_type(&self) -> Option<FieldType> {
+        FieldType::of(self.ty())
+    }
 
     fn ident(&self) -> Option<&Ident> {
         self.ident.as_ref()
     }
 
-    /*fn ty(&self) -> &Type {
+    fn ty(&self) -> &Type {
         &self.ty
-    }*/
+    }
 
-    fn size(&self) -> usize {
-        self.size
+    fn serializer(&self) -> darling::Result<Option<syn::Path>> {
+        match (&self.serialize_with, &self.with) {
+            (Some(_), Some(_)) => {
+                Err(self.error("serialize_with and with can not be used together"))
+            }
+            (Some(serialize_with), None) => Ok(Some(serialize_with.clone())),
+            (None, Some(with)) => Ok(Some(syn::parse_quote!(#with::serialize))),
+            (None, None) => Ok(None),
+        }
     }
 
-    fn pad(&self) -> char {
-        self.pad
+    fn deserializer(&self, parse: bool) -> darling::Result<Option<syn::Path>> {
+        match (&self.deserialize_with, &self.with) {
+            (Some(_), Some(_)) => {
+                Err(self.error("deserialize_with and with can not be used together"))
+            }
+            (Some(_), None) if !parse => {
+                Err(self.error("deserialize_with is only used when the struct has parse"))
+            }
+            (Some(deserialize_with), None) => Ok(Some(deserialize_with.clone())),
+            (None, Some(with)) if parse => Ok(Some(syn::parse_quote!(#with::deserialize))),
+            (None, None) if parse && self.serialize_with.is_some() => {
+                Err(self
+                    .error("a field with serialize_with needs a deserialize_with to be read back"))
+            }
+            (None, _) => Ok(None),
+        }
     }
 
-    fn pad_left(&self) -> bool {
-        self.pad_left
+    /// Returns the size of the field, `None` for a nested record that takes the length of the child.
+    fn size(&self) -> darling::Result<Option<usize>> {
+        if self.depends_on.is_some() {
+            if self.size.is_some() || self.end.is_some() {
+                return Err(self.error("a depends_on group has a variable length and no size"));
+            }
+            return Ok(None);
+        }
+
+        let size = match (self.size, self.start, self.end) {
+            (_, None, Some(_)) => return Err(self.error("end requires start")),
+            (size, Some(start), Some(end)) => {
+                if end < start {
+                    return Err(self.error(format!("end {} is before start {}", end, start)));
+                }
+                let len = end - start + 1;
+                if size.is_some_and(|size| size != len) {
+                    return Err(self.error(format!(
+                        "size {} does not match columns {}-{}",
+                        size.unwrap(),
+                        start,
+                        end
+                    )));
+                }
+                len
+            }
+            (Some(size), _, None) => size,
+            (None, _, None) if self.nested || self.occurs.is_some() => return Ok(None),
+            (None, _, None) => return Err(self.error("missing size (or start/end)")),
+        };
+
+        Ok(Some(size))
     }
 
-    fn decimals(&self) -> usize {
-        self.decimals
+    fn has_rules(&self) -> bool {
+        self.min.is_some()
+            || self.max.is_some()
+            || self.pattern.is_some()
+            || self.required
+            || self.one_of.is_some()
+    }
+
+    fn is_pointer(&self) -> bool {
+        is_pointer(self.ty())
+    }
+
+    // an Option<&T> or Option<Box<T>>
+    fn is_optional_pointer(&self) -> bool {
+        type_argument(self.ty(), "Option").is_some_and(is_pointer)
+    }
+
+    // the record type of a nested field and whether it is wrapped in an Option
+    fn nested_type(&self) -> (&Type, bool) {
+        match type_argument(self.ty(), "Option") {
+            Some(inner) => (inner, true),
+            None => (self.ty(), false),
+        }
+    }
+
+    // the record type of an occurs field, i.e. the element of a Vec<T> or [T; N]
+    fn element_type(&self) -> Option<&Type> {
+        match self.ty() {
+            Type::Array(array) => Some(&array.elem),
+            ty => type_argument(ty, "Vec"),
+        }
     }
 
-    fn date_format(&self) -> &str {
-        self.date_format.as_ref()
+    // N of a [T; N] field, when it is a literal (a const is only known to the compiler)
+    fn array_len(&self) -> Option<usize> {
+        let Type::Array(array) = self.ty() else {
+            return None;
+        };
+        match &array.len {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(len),
+                ..
+            }) => len.base10_parse().ok(),
+            _ => None,
+        }
     }
 
-    fn time_format(&self) -> &str {
-        self.time_format.as_ref()
+    fn error<T: std::fmt::Display>(&self, msg: T) -> darling::Error {
+        let error = darling::Error::custom(msg);
+        match self.ident() {
+            Some(ident) => error.with_span(ident),
+            None => error.with_span(self.ty()),
+        }
     }
+}
+
+fn is_pointer(ty: &Type) -> bool {
+    matches!(ty, Type::Reference(_)) || type_argument(ty, "Box").is_some()
+}
 
-    fn date_time_format(&self) -> &str {
-        self.date_time_format.as_ref()
+// `T` when `ty` is `wrapper<T>`
+fn type_argument<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
     }
 }
 
-#[derive(Debug, Clone, Copy, EnumString, strum::Display)]
+// type of a field, guessed from the last segment of its type path (Option, Box, Arc and references are looked through).
+// Types that are not listed here (enums, nested records, aliases) get no type specific defaults
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, strum::Display)]
 enum FieldType {
-    String,
+    #[strum(
+        serialize = "String",
+        serialize = "str",
+        serialize = "char",
+        serialize = "Cow",
+        serialize = "IpAddr",
+        serialize = "Ipv4Addr",
+        serialize = "Ipv6Addr",
+        serialize = "Uuid"
+    )]
+    Text,
+    #[strum(
+        serialize = "u8",
+        serialize = "u16",
+        serialize = "u32",
+        serialize = "u64",
+        serialize = "u128",
+        serialize = "usize",
+        serialize = "i8",
+        serialize = "i16",
+        serialize = "i32",
+        serialize = "i64",
+        serialize = "i128",
+        serialize = "isize",
+        serialize = "f32",
+        serialize = "f64",
+        serialize = "BigDecimal",
+        serialize = "Duration",
+        serialize = "TimeDelta"
+    )]
+    Number,
+    #[strum(
+        serialize = "Date",
+        serialize = "Time",
+        serialize = "PrimitiveDateTime",
+        serialize = "NaiveDate",
+        serialize = "NaiveTime",
+        serialize = "NaiveDateTime",
+        serialize = "OffsetDateTime",
+        serialize = "UtcDateTime",
+        serialize = "DateTime"
+    )]
+    Date,
+    #[strum(serialize = "bool")]
+    Bool,
+}
+
+impl FieldType {
+    fn of(ty: &Type) -> Option<FieldType> {
+        match ty {
+            Type::Reference(reference) => FieldType::of(&reference.elem),
+            Type::Paren(paren) => FieldType::of(&paren.elem),
+            Type::Path(path) => {
+                let segment = path.path.segments.last()?;
+                if segment.ident == "Option" || segment.ident == "Box" || segment.ident == "Arc" {
+                    if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                        if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                            return FieldType::of(inner);
+                        }
+                    }
+                }
+                FieldType::from_str(&segment.ident.to_string()).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+// `true = "S"` is not a path syn can parse, so in the fixed_width attributes the keys are renamed
+// to true_value and false_value (which can also be written directly) before darling reads them
+fn rename_bool_keys(input: &mut DeriveInput) {
+    fn rename(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut res = Vec::with_capacity(tokens.len());
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                TokenTree::Ident(ident)
+                    if (ident == "true" || ident == "false")
+                        && matches!(tokens.get(i + 1), Some(TokenTree::Punct(p)) if p.as_char() == '=') =>
+                {
+                    let key = Ident::new(&format!("{}_value", ident), ident.span());
+                    res.push(TokenTree::Ident(key));
+                }
+                TokenTree::Group(group) => {
+                    let mut renamed =
+                        proc_macro2::Group::new(group.delimiter(), rename(group.stream()));
+                    renamed.set_span(group.span());
+                    res.push(TokenTree::Group(renamed));
+                }
+                token => res.push(token.clone()),
+            }
+        }
+        res.into_iter().collect()
+    }
+
+    let fields = match &mut input.data {
+        syn::Data::Struct(data) => data.fields.iter_mut().collect(),
+        _ => Vec::new(),
+    };
+    let attrs = input
+        .attrs
+        .iter_mut()
+        .chain(fields.into_iter().flat_map(|field| field.attrs.iter_mut()));
+    for attr in attrs {
+        if let syn::Meta::List(list) = &mut attr.meta {
+            if list.path.is_ident("fixed_width") {
+                list.tokens = rename(std::mem::take(&mut list.tokens));
+            }
+        }
+    }
 }
 
 #[proc_macro_derive(FixedWidth, attributes(fixed_width))]
 pub fn derive(input: TokenStream) -> TokenStream {
-    let input: DeriveInput = parse_macro_input!(input);
-    let fw: FixedWidthFields = FixedWidthFields::from_derive_input(&input).unwrap();
-    //println!("Derive {:#?}", fw);
-    //let DeriveInput { ident, .. } = input;
+    let mut input: DeriveInput = parse_macro_input!(input);
+    rename_bool_keys(&mut input);
     //println!("Derive {:#?}", input);
-    let ident = input.ident;
 
-    let mut fields = Vec::new();
+    // every problem is reported as a compile_error! pointing at the offending field or attribute
+    match FixedWidthFields::from_derive_input(&input).and_then(|fw| expand_fixed_width(&fw)) {
+        Ok(output) => output.into(),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
+fn expand_fixed_width(fw: &FixedWidthFields) -> darling::Result<proc_macro2::TokenStream> {
+    let mut errors = darling::Error::accumulator();
+    let ident = &fw.ident;
+    let (impl_generics, ty_generics, where_clause) = fw.generics.split_for_impl();
 
-    for field in fw.data.take_struct().unwrap() {
-        let field_name = field.field_name();
+    let mut literals: Vec<Literal> = fw
+        .fillers
+        .iter()
+        .filter_map(|filler| errors.handle(Literal::from_filler(filler)))
+        .collect();
+    literals.extend(fw.constants.iter().map(Literal::from_constant));
+    literals.sort_by_key(|literal| literal.start);
+    let mut literals = literals.into_iter().peekable();
 
-        let field_name_ts: proc_macro2::TokenStream = field_name.parse().unwrap();
-        let size = field.size();
-        let pad = field.pad() as u8;
-        let pad_left = field.pad_left();
-        let decimals = field.decimals();
-        let date_format = field.date_format();
-        let time_format = field.time_format();
-        let date_time_format = field.date_time_format();
+    // a char past ASCII is more than one byte, the gap would not be filled to the column
+    if let Some(gap_filler) = fw
+        .gap_filler
+        .as_ref()
+        .filter(|gap_filler| !gap_filler.is_ascii())
+    {
+        errors.push(
+            darling::Error::custom(format!(
+                "gap_filler '{}' is not an ASCII char",
+                **gap_filler
+            ))
+            .with_span(&gap_filler.span()),
+        );
+    }
+
+    let mut layout = Layout {
+        record_len: 0,
+        nested_lens: Vec::new(),
+        open_ended: None,
+        gap_filler: fw.gap_filler.as_deref().copied(),
+        segments: Vec::new(),
+        reads: Vec::new(),
+    };
 
-        let convert = quote! {
-            let mut v = fixed_width::pad(&self.#field_name_ts, #field_name, #size, #pad, #pad_left, #decimals, #date_format, #time_format, #date_time_format)?;
-            res.append(&mut v);
+    let fields = fw
+        .data
+        .as_ref()
+        .take_struct()
+        .ok_or_else(|| darling::Error::unsupported_shape("enum").with_span(ident))?;
+
+    // count fields, written as the length of the group that depends on them
+    let counts: Vec<(&String, syn::Member)> = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !field.skip)
+        .filter_map(|(index, field)| Some((field.depends_on.as_ref()?, field.member(index))))
+        .collect();
+    let mut written = Vec::new();
+    let mut validations = Vec::new();
+
+    // the fields read back, in the order of the record, and how the struct is built from them
+    let mut read_members = Vec::new();
+
+    for (index, field) in fields.iter().enumerate() {
+        if field.skip {
+            let member = field.member(index);
+            read_members.push(quote!(#member: Default::default()));
+            continue;
+        }
+        let Some(deserializer) = errors.handle(field.deserializer(fw.parse)) else {
+            continue;
+        };
+        let field_name = field.field_name(index);
+        let group = counts
+            .iter()
+            .find(|(count, _)| **count == field_name)
+            .map(|(_, group)| group);
+        written.push(field_name.clone());
+
+        // fillers and constants declared before this field go first
+        while let Some(literal) = literals
+            .next_if(|literal| literal.start <= field.start.unwrap_or(layout.record_len + 1))
+        {
+            if let Err(e) = layout.place_literal(&literal) {
+                errors.push(literal.error(e));
+            }
+        }
+
+        let Some(size) = errors.handle(field.size()) else {
+            continue;
+        };
+        // nested records and repeating groups are written through the FixedWidth impl of their record type
+        let record_type = match (field.occurs, &field.depends_on) {
+            (_, Some(_)) => match type_argument(field.ty(), "Vec") {
+                Some(element_type) => Some(element_type),
+                None => {
+                    errors.push(field.error("depends_on requires a Vec<T> field"));
+                    continue;
+                }
+            },
+            (Some(occurs), None) => match field.element_type() {
+                // an array always holds N records, a different occurs would drop or blank some of them
+                Some(_) if field.array_len().is_some_and(|len| len != occurs) => {
+                    errors.push(field.error(format!(
+                        "occurs is {} but the array holds {} records",
+                        occurs,
+                        field.array_len().unwrap()
+                    )));
+                    continue;
+                }
+                Some(element_type) => Some(element_type),
+                None => {
+                    errors.push(field.error("occurs requires a Vec<T> or [T; N] field"));
+                    continue;
+                }
+            },
+            (None, None) if field.nested => Some(field.nested_type().0),
+            (None, None) => None,
+        };
+        let customized = field.compute.is_some()
+            || field.serialize_with.is_some()
+            || field.with.is_some()
+            || field.deserialize_with.is_some();
+        if record_type.is_some() && customized {
+            errors.push(field.error(
+                "nested, occurs and depends_on can not be combined with compute, serialize_with, deserialize_with or with",
+            ));
+            continue;
+        }
+        if field.has_rules()
+            && (record_type.is_some() || field.serialize_with.is_some() || field.with.is_some())
+        {
+            errors.push(field.error(
+                "validation rules apply to plain fields, not to nested records or custom serializers",
+            ));
+            continue;
+        }
+        if group.is_some() && (record_type.is_some() || customized) {
+            errors.push(field.error(
+                "a depends_on count is filled in from its group and can only be a plain field",
+            ));
+            continue;
+        }
+
+        // the size of a nested record in a generic struct can change with every instantiation
+        let size_per_instance =
+            fw.generics.type_params().next().is_some() && size.is_none() && record_type.is_some();
+        let placed = match (size, record_type) {
+            _ if field.depends_on.is_some() => {
+                layout.place_variable(field.start).map(|_| quote!(0))
+            }
+            (Some(size), _) => layout.place(field.start, size).map(|_| quote!(#size)),
+            (None, Some(record_type)) => {
+                let len = match field.occurs {
+                    Some(occurs) => {
+                        quote!(#occurs * <#record_type as fixed_width::FixedWidthLayout>::RECORD_LEN)
+                    }
+                    None => quote!(<#record_type as fixed_width::FixedWidthLayout>::RECORD_LEN),
+                };
+                layout.place_nested(field.start, len.clone()).map(|_| len)
+            }
+            (None, None) => unreachable!("only nested and occurs fields can leave out the size"),
+        };
+        let size = match placed {
+            Ok(size) => size,
+            Err(e) => {
+                errors.push(field.error(format!("field {}", e)));
+                continue;
+            }
+        };
+
+        let member = field.member(index);
+        let field_type = field.field_type();
+        let options = field
+            .options
+            .clone()
+            .or(fw.type_defaults(field_type))
+            .or(Some(&fw.defaults))
+            .or(fw.standard_defaults(field_type).as_ref());
+        // a char past ASCII is more than one byte, the padding would not fill the field
+        let pad = match options.pad() {
+            pad if !pad.is_ascii() => {
+                errors.push(field.error(format!("pad '{}' is not an ASCII char", pad)));
+                continue;
+            }
+            pad => pad as u8,
+        };
+        let pad_left = options.pad_left();
+        let decimals = options.decimals();
+        let date_format = options.date_format();
+        let time_format = options.time_format();
+        let date_time_format = options.date_time_format();
+        let true_value = options.true_value();
+        let false_value = options.false_value();
+        let null = options.null.as_ref().map(|null| quote!(.with_null(#null)));
+        let null_fill = match options.null_fill {
+            // a char past ASCII is more than one byte, a None would not fill the field
+            Some(null_fill) if !null_fill.is_ascii() => {
+                errors.push(field.error(format!("null_fill '{}' is not an ASCII char", null_fill)));
+                continue;
+            }
+            Some(null_fill) => {
+                let null_fill = null_fill as u8;
+                Some(quote!(.with_null_fill(#null_fill)))
+            }
+            None => None,
+        };
+        let offset = match options.offset.as_deref().map(parse_offset) {
+            Some(Some(offset)) => Some(quote!(.with_offset(#offset))),
+            Some(None) => {
+                errors.push(field.error("offset must be Z, UTC or a [+-]HH:MM offset up to 23:59"));
+                continue;
+            }
+            None => None,
+        };
+        let date_encoding = match options.date_encoding.as_deref().map(DateEncoding::from_str) {
+            Some(Ok(date_encoding)) => {
+                let date_encoding = Ident::new(
+                    &format!("{:?}", date_encoding),
+                    proc_macro2::Span::call_site(),
+                );
+                Some(
+                    quote!(.with_date_encoding(fixed_width::model::date_encoding::DateEncoding::#date_encoding)),
+                )
+            }
+            Some(Err(_)) => {
+                errors.push(field.error(
+                    "date_encoding must be one of format, ordinal, short_ordinal, short_year, epoch_days",
+                ));
+                continue;
+            }
+            None => None,
+        };
+        let duration_format = match options
+            .duration_format
+            .as_deref()
+            .map(DurationFormat::from_str)
+        {
+            Some(Ok(duration_format)) => {
+                let duration_format = Ident::new(
+                    &format!("{:?}", duration_format),
+                    proc_macro2::Span::call_site(),
+                );
+                Some(
+                    quote!(.with_duration_format(fixed_width::model::duration_format::DurationFormat::#duration_format)),
+                )
+            }
+            Some(Err(_)) => {
+                errors.push(field.error(
+                    "duration_format must be one of seconds, milliseconds, minutes, hours, hhmmss, hhmm",
+                ));
+                continue;
+            }
+            None => None,
+        };
+        let simple_uuid = match options.uuid_format.as_deref() {
+            Some("simple") => Some(quote!(.with_simple_uuid(true))),
+            Some("hyphenated") | None => None,
+            Some(_) => {
+                errors.push(field.error("uuid_format must be hyphenated or simple"));
+                continue;
+            }
+        };
+        let case = match options.case.as_deref().map(TextCase::from_str) {
+            Some(Ok(case)) => {
+                let case = Ident::new(&format!("{:?}", case), proc_macro2::Span::call_site());
+                Some(quote!(.with_case(fixed_width::model::text_transform::TextCase::#case)))
+            }
+            Some(Err(_)) => {
+                errors.push(field.error("case must be upper or lower"));
+                continue;
+            }
+            None => None,
+        };
+        // nested records are written by their own fields, already transformed and padded
+        let case = case.filter(|_| record_type.is_none());
+        let text_transforms = [
+            ("with_trim", options.trim),
+            ("with_collapse_whitespace", options.collapse_whitespace),
+            ("with_transliterate", options.transliterate),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled == Some(true) && record_type.is_none())
+        .map(|(method, _)| {
+            let method = Ident::new(method, proc_macro2::Span::call_site());
+            quote!(.#method(true))
+        });
+        let charset = match options.charset.as_deref().map(parse_charset) {
+            Some(Some(charset)) => Some(quote!(.with_charset(#charset))),
+            Some(None) => {
+                errors.push(field.error(
+                    "charset must be numeric, alpha, alnum, ascii_printable or a set such as \"[A-Z0-9 ]\"",
+                ));
+                continue;
+            }
+            None => None,
+        };
+        let charset = charset.filter(|_| record_type.is_none());
+        let min = field
+            .min
+            .as_ref()
+            .map(|Bound(min)| quote!(.with_min(#min.parse().expect("min checked by the derive"))));
+        let max = field
+            .max
+            .as_ref()
+            .map(|Bound(max)| quote!(.with_max(#max.parse().expect("max checked by the derive"))));
+        let pattern = match field.pattern.as_ref().map(|p| (p, regex::Regex::new(p))) {
+            Some((pattern, Ok(_))) => Some(quote!(.with_pattern(#pattern))),
+            Some((_, Err(e))) => {
+                errors.push(field.error(format!("invalid pattern: {}", e)));
+                continue;
+            }
+            None => None,
+        };
+        let required = field.required.then(|| quote!(.with_required(true)));
+        let one_of = field
+            .one_of
+            .as_ref()
+            .map(|OneOf(values)| quote!(.with_one_of(&[#(#values),*])));
+        let pivot = options.pivot.map(|pivot| quote!(.with_pivot(#pivot)));
+        let epoch = match options.epoch.as_deref().map(parse_epoch) {
+            Some(Some(julian_day)) => Some(quote!(
+                .with_epoch(fixed_width::time::Date::from_julian_day(#julian_day).expect("epoch checked by the derive"))
+            )),
+            Some(None) => {
+                errors.push(field.error("epoch must be a YYYY-MM-DD date"));
+                continue;
+            }
+            None => None,
         };
-        fields.push(convert);
+
+        // nested records are validated through their own fields, the path tells where they are
+        let path = quote!(fixed_width::model::validation::field_path(path, #field_name));
+        let validation = if field.depends_on.is_some() || field.occurs.is_some() {
+            Some(quote! {
+                let path = #path;
+                for (i, value) in this.#member.iter().enumerate() {
+                    fixed_width::FixedWidth::validate_into(value, &format!("{}[{}]", path, i), violations);
+                }
+            })
+        } else if record_type.is_some() {
+            let value = match field.nested_type().1 {
+                true => quote! { this.#member.as_ref() },
+                false => quote! { Some(&this.#member) },
+            };
+            Some(quote! {
+                if let Some(value) = #value {
+                    fixed_width::FixedWidth::validate_into(value, &#path, violations);
+                }
+            })
+        } else if field.has_rules() {
+            let value = match (&field.compute, group) {
+                (Some(compute), _) => quote! { &#compute(this) },
+                (None, Some(group)) => quote! { &(this.#group.len() as u64) },
+                (None, None) if field.is_pointer() => quote! { &*this.#member },
+                (None, None) if field.is_optional_pointer() => {
+                    quote! { &fixed_width::any_value::OptionRef(this.#member.as_deref()) }
+                }
+                (None, None) => quote! { &this.#member },
+            };
+            Some(quote! {
+                field_config().validate(#value, &#path, violations);
+            })
+        } else {
+            None
+        };
+
+        // `this` is None when the blank layout of the record is written
+        let write = if field.depends_on.is_some() {
+            let max = match field.occurs {
+                Some(occurs) => quote!(Some(#occurs)),
+                None => quote!(None),
+            };
+            quote! {
+                let records = this.map(|this| &this.#member[..]).unwrap_or_default();
+                fixed_width::write_depending(records, #max, field_config, res)?;
+            }
+        } else if let (Some(occurs), Some(record_type)) = (field.occurs, record_type) {
+            quote! {
+                fixed_width::write_padded(field_config, res, |res| {
+                    let records = this.map(|this| &this.#member[..]).unwrap_or_default();
+                    fixed_width::write_occurs(records, #occurs, field_config, res, <#record_type as fixed_width::FixedWidthLayout>::extend_blank_into)
+                })?;
+            }
+        } else if let Some(record_type) = record_type {
+            let value = match field.nested_type().1 {
+                true => quote! { this.and_then(|this| this.#member.as_ref()) },
+                false => quote! { this.map(|this| &this.#member) },
+            };
+            quote! {
+                match #value {
+                    Some(value) => fixed_width::write_padded(field_config, res, |res| {
+                        fixed_width::FixedWidth::extend_into(value, res)
+                    })?,
+                    None => fixed_width::write_padded(field_config, res, <#record_type as fixed_width::FixedWidthLayout>::extend_blank_into)?,
+                }
+            }
+        } else {
+            let value = match (&field.compute, group) {
+                (Some(compute), _) => quote! { &#compute(this) },
+                (None, Some(group)) => quote! { &(this.#group.len() as u64) },
+                // AnyValueTrait can not be implemented for every &T and Box<T>, so they are dereferenced here
+                (None, None) if field.is_pointer() => quote! { &*this.#member },
+                (None, None) if field.is_optional_pointer() => {
+                    quote! { &fixed_width::any_value::OptionRef(this.#member.as_deref()) }
+                }
+                (None, None) => quote! { &this.#member },
+            };
+            // a computed field or a count is only a placeholder, reading it keeps the dead code lint quiet
+            let placeholder = (field.compute.is_some() || group.is_some()).then(|| {
+                quote! {
+                    let _ = &this.#member;
+                }
+            });
+            let write_value = match errors.handle(field.serializer()) {
+                // a serializer gets the Option itself, not the OptionRef
+                Some(Some(serializer)) if field.is_optional_pointer() => quote! {
+                    fixed_width::write_field_with(&this.#member, field_config, res, #serializer)?;
+                },
+                Some(Some(serializer)) => quote! {
+                    fixed_width::write_field_with(#value, field_config, res, #serializer)?;
+                },
+                _ => quote! {
+                    fixed_width::write_field(#value, field_config, res)?;
+                },
+            };
+            quote! {
+                match this {
+                    Some(this) => {
+                        #placeholder
+                        #write_value
+                    }
+                    None => fixed_width::write_field(&fixed_width::any_value::AnyValueNull::new(), field_config, res)?,
+                }
+            }
+        };
+
+        // how the value is read back, with the count of the group for a depends_on count
+        let var = format_ident!("field_{}", index);
+        let read = if let Some(count) = &field.depends_on {
+            let count = format_ident!("count_{}", count);
+            let max = match field.occurs {
+                Some(occurs) => quote!(Some(#occurs)),
+                None => quote!(None),
+            };
+            quote! {
+                let #var = fixed_width::read_depending(bytes, &mut pos, #count, #max, field_config)?;
+            }
+        } else if let (Some(occurs), Some(record_type)) = (field.occurs, record_type) {
+            // the length of an array is in its type
+            let read_occurs = match field.ty() {
+                Type::Array(_) => quote! {
+                    fixed_width::read_occurs_array::<#record_type, #occurs>(bytes, &mut pos, field_config)
+                },
+                _ => quote! {
+                    fixed_width::read_occurs::<#record_type>(bytes, &mut pos, #occurs, field_config)
+                },
+            };
+            quote! {
+                let #var = #read_occurs?;
+            }
+        } else if let Some(record_type) = record_type {
+            let read_nested = match field.nested_type().1 {
+                true => quote!(read_optional_nested),
+                false => quote!(read_nested),
+            };
+            quote! {
+                let #var = fixed_width::#read_nested::<#record_type>(bytes, &mut pos, field_config)?;
+            }
+        } else if let Some(deserializer) = deserializer {
+            quote! {
+                let #var = fixed_width::read_field_with(bytes, &mut pos, field_config, #deserializer)?;
+            }
+        } else if field.compute.is_some() {
+            // the value is computed again on write, what the record holds is only skipped over
+            quote! {
+                fixed_width::read_text(bytes, &mut pos, field_config)?;
+                let #var = Default::default();
+            }
+        } else if group.is_some() {
+            let count = format_ident!("count_{}", field_name);
+            quote! {
+                let text = fixed_width::read_text(bytes, &mut pos, field_config)?;
+                let #var = fixed_width::from_value::FromFieldValue::from_field_value(text, field_config)?;
+                let #count = <usize as fixed_width::from_value::FromFieldValue>::from_field_value(text, field_config)?;
+            }
+        } else {
+            quote! {
+                let #var = fixed_width::read_field(bytes, &mut pos, field_config)?;
+            }
+        };
+        read_members.push(quote!(#member: #var));
+
+        let new_field_config = quote! {
+            fixed_width::model::field_config::FieldConfig::new(#field_name, #size, #pad, #pad_left, #decimals, #date_format, #time_format, #date_time_format)
+                .with_bool(#true_value, #false_value)
+                #null
+                #null_fill
+                #offset
+                #date_encoding
+                #pivot
+                #epoch
+                #duration_format
+                #simple_uuid
+                #case
+                #(#text_transforms)*
+                #charset
+                #min
+                #max
+                #pattern
+                #required
+                #one_of
+        };
+        // the config (and the date/time format descriptions cached inside it) is built once per field,
+        // not once per record; a static is shared by every instantiation of a generic struct, so a size
+        // that depends on the type parameters gets its config built on every call instead
+        let (field_config, get_field_config) = match size_per_instance {
+            true => (
+                quote! {
+                    let field_config = || #new_field_config;
+                },
+                quote!(&field_config()),
+            ),
+            false => (
+                quote! {
+                    let field_config = || {
+                        static FIELD_CONFIG: std::sync::OnceLock<fixed_width::model::field_config::FieldConfig> = std::sync::OnceLock::new();
+                        FIELD_CONFIG.get_or_init(|| #new_field_config)
+                    };
+                },
+                quote!(field_config()),
+            ),
+        };
+        layout.segments.push(quote! {
+            {
+                #field_config
+                let field_config = #get_field_config;
+                #write
+            }
+        });
+        // the block only scopes the config, the values it reads are used to build the record
+        layout.reads.push(quote! {
+            #field_config
+            let field_config = #get_field_config;
+            #read
+        });
+        if let Some(validation) = validation {
+            let field_config = field.has_rules().then_some(field_config);
+            validations.push(quote! {
+                {
+                    #field_config
+                    #validation
+                }
+            });
+        }
     }
 
-    let output: proc_macro2::TokenStream = quote! {
-        impl FixedWidth for #ident {
+    for literal in literals {
+        if let Err(e) = layout.place_literal(&literal) {
+            errors.push(literal.error(e));
+        }
+    }
+    for (index, field) in fields.iter().enumerate().filter(|(_, field)| !field.skip) {
+        if let Some(count) = &field.depends_on {
+            let position = |name: &String| written.iter().position(|written| written == name);
+            match position(count) {
+                None => errors.push(field.error(format!("depends_on field {} not found", count))),
+                // the count has to be read before the group it sizes
+                Some(count_position)
+                    if fw.parse && Some(count_position) > position(&field.field_name(index)) =>
+                {
+                    errors.push(field.error(format!(
+                        "depends_on field {} has to come before the group to be read back",
+                        count
+                    )))
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    let mut record_len = layout.record_len();
+    let mut record_len_check = None;
+    let variable = !counts.is_empty();
+    if let Some(expected) = &fw.record_len {
+        let expected_len = **expected;
+        if variable {
+            errors.push(
+                darling::Error::custom(
+                    "record_len can not be checked on a record with a depends_on group",
+                )
+                .with_span(&expected.span()),
+            );
+        } else if layout.nested_lens.is_empty() {
+            if expected_len != layout.record_len {
+                errors.push(
+                    darling::Error::custom(format!(
+                        "record_len is {} but the field sizes add up to {}",
+                        expected_len, layout.record_len
+                    ))
+                    .with_span(&expected.span()),
+                );
+            }
+        } else {
+            // the length of the nested records is only known to the compiler, so the check is left to it
+            let message = format!(
+                "record_len is {} but the field sizes add up to a different length",
+                expected_len
+            );
+            match fw.generics.params.is_empty() {
+                // a free const is evaluated by every build, cargo check included
+                true => {
+                    record_len_check = Some(quote! {
+                        const _: () = assert!(<#ident>::RECORD_LEN == #expected_len, #message);
+                    })
+                }
+                // a generic record has no length until it is instantiated, so the check only fires
+                // when RECORD_LEN of an instance is used in a full build (cargo check skips it)
+                false => {
+                    record_len = quote! {
+                        {
+                            let record_len = #record_len;
+                            assert!(record_len == #expected_len, #message);
+                            record_len
+                        }
+                    }
+                }
+            }
+        }
+    }
+    errors.finish()?;
+
+    let fields = layout.segments;
+    let reads = layout.reads;
+    let from_fixed_width = fw.parse.then(|| {
+        quote! {
+            impl #impl_generics fixed_width::FromFixedWidth for #ident #ty_generics #where_clause {
+                fn read_fixed_width(bytes: &[u8]) -> Result<(Self, usize), fixed_width::error::FixedWidthError> {
+                    let mut pos = 0;
+                    #(#reads)*
+                    Ok((Self { #(#read_members),* }, pos))
+                }
+            }
+        }
+    });
+    let validate = fw
+        .validate
+        .then(|| quote!(fixed_width::FixedWidth::validate(self)?;));
+
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Length in bytes of every record, i.e. the sum of the field sizes (depends_on groups, whose length varies, excluded).
+            /// With nested records, a `record_len` of a generic record is only checked when a full build uses this const.
+            pub const RECORD_LEN: usize = #record_len;
+
+            /// Appends the blank layout of the record: every field empty and padded, fillers and constants included.
+            /// This is what a `None` nested record is written as.
+            pub fn extend_blank_into(res: &mut Vec<u8>) -> Result<(), fixed_width::error::FixedWidthError> {
+                Self::write_fixed_width_fields(None, res)
+            }
+
+            fn write_fixed_width_fields(this: Option<&Self>, res: &mut Vec<u8>) -> Result<(), fixed_width::error::FixedWidthError> {
+                let start = res.len();
+                let mut write_fields = || -> Result<(), fixed_width::error::FixedWidthError> {
+                    #(#fields)*
+                    Ok(())
+                };
+
+                // never leave half a record in the caller's buffer
+                write_fields().inspect_err(|_| res.truncate(start))
+            }
+        }
+
+        impl #impl_generics FixedWidth for #ident #ty_generics #where_clause {
             fn to_fixed_width_bytes(&self) -> Result<Vec<u8>, fixed_width::error::FixedWidthError> {
-                let mut s = String::new();
-                let mut res: Vec<u8> = Vec::new();
-                #(#fields)*
+                let mut res: Vec<u8> = Vec::with_capacity(Self::RECORD_LEN);
+                self.extend_into(&mut res)?;
                 Ok(res)
             }
+
+            fn extend_into(&self, res: &mut Vec<u8>) -> Result<(), fixed_width::error::FixedWidthError> {
+                #validate
+                Self::write_fixed_width_fields(Some(self), res)
+            }
+
+            #[allow(unused_variables)]
+            fn validate_into(&self, path: &str, violations: &mut Vec<fixed_width::model::validation::Violation>) {
+                let this = self;
+                #(#validations)*
+            }
+        }
+
+        #record_len_check
+
+        #from_fixed_width
+
+        impl #impl_generics fixed_width::FixedWidthLayout for #ident #ty_generics #where_clause {
+            const RECORD_LEN: usize = <#ident #ty_generics>::RECORD_LEN;
+
+            fn extend_blank_into(res: &mut Vec<u8>) -> Result<(), fixed_width::error::FixedWidthError> {
+                <#ident #ty_generics>::extend_blank_into(res)
+            }
         }
-    };
 
-    output.into()
+        // a record can also be the value of a field, e.g. an Option<Self> or a Vec<Self> in a parent record
+        impl #impl_generics fixed_width::any_value::AnyValueTrait for #ident #ty_generics #where_clause {
+            fn into_any_value(&self) -> Result<fixed_width::any_value::AnyValue, fixed_width::error::FixedWidthError> {
+                Ok(fixed_width::any_value::AnyValue::Bytes(self.to_fixed_width_bytes()?))
+            }
+
+            fn write_value(
+                &self,
+                _: &fixed_width::model::field_config::FieldConfig,
+                res: &mut Vec<u8>,
+            ) -> Result<(), fixed_width::error::FixedWidthError> {
+                self.extend_into(res)
+            }
+            fn is_record(&self) -> bool {
+                true
+            }
+        }
+    })
 }
 
 // FIXED WIDTH ENUM DERIVE
 
-/*#[derive(Debug, FromDeriveInput)]
-#[darling(supports(enum_any))]
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(fixed_width))]
 struct FixedWidthEnumFields {
     ident: Ident,
-    data: ast::Data<util::Ignored, FixedWidthEnumField>,
-}*/
-
-//#[darling(default)]
-//skip: bool,
+    data: ast::Data<FixedWidthEnumField, util::Ignored>,
+    // casing applied to the variant names, e.g. #[fixed_width(rename_all = "SCREAMING_SNAKE_CASE")]
+    rename_all: Option<String>,
+    // the key of every variant is its integer discriminant, e.g. A = 1 is written as "1"
+    #[darling(default)]
+    discriminant_keys: bool,
+}
 
 #[derive(Debug, FromVariant)]
-#[darling(from_ident, attributes(hello))]
-#[allow(dead_code)]
+#[darling(attributes(fixed_width))]
 struct FixedWidthEnumField {
     ident: syn::Ident,
-    into: Option<bool>,
-    skip: Option<bool>,
     discriminant: Option<syn::Expr>,
     fields: darling::ast::Fields<syn::Type>,
+    // key written for the variant, instead of its name
+    key: Option<String>,
+    // catch-all Other(String) variant, holding any key not matched by the other variants
+    #[darling(default)]
+    other: bool,
+}
+
+// casing of the variant names, same names as serde's rename_all
+#[derive(Debug, Clone, Copy, EnumString)]
+enum Casing {
+    #[strum(serialize = "lowercase")]
+    Lower,
+    #[strum(serialize = "UPPERCASE")]
+    Upper,
+    #[strum(serialize = "PascalCase")]
+    Pascal,
+    #[strum(serialize = "camelCase")]
+    Camel,
+    #[strum(serialize = "snake_case")]
+    Snake,
+    #[strum(serialize = "SCREAMING_SNAKE_CASE")]
+    ScreamingSnake,
+    #[strum(serialize = "kebab-case")]
+    Kebab,
+    #[strum(serialize = "SCREAMING-KEBAB-CASE")]
+    ScreamingKebab,
 }
-impl From<syn::Ident> for FixedWidthEnumField {
-    fn from(ident: syn::Ident) -> Self {
-        FixedWidthEnumField {
-            ident,
-            into: Default::default(),
-            skip: Default::default(),
-            discriminant: None,
-            fields: darling::ast::Style::Unit.into(),
+
+impl Casing {
+    // variant names are PascalCase, so words start at every uppercase letter
+    fn apply(self, name: &str) -> String {
+        let words = |separator: char| {
+            let mut res = String::new();
+            for (i, c) in name.chars().enumerate() {
+                if i > 0 && c.is_uppercase() {
+                    res.push(separator);
+                }
+                res.push(c.to_ascii_lowercase());
+            }
+            res
+        };
+
+        match self {
+            Casing::Lower => name.to_ascii_lowercase(),
+            Casing::Upper => name.to_ascii_uppercase(),
+            Casing::Pascal => name.to_string(),
+            Casing::Camel => {
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Casing::Snake => words('_'),
+            Casing::ScreamingSnake => words('_').to_ascii_uppercase(),
+            Casing::Kebab => words('-'),
+            Casing::ScreamingKebab => words('-').to_ascii_uppercase(),
         }
     }
 }
 
-/*impl FixedWidthEnumField {
-    fn field_name(&self) -> String {
-        self.ident()
-            .and_then(|i| Some(i.to_string()))
-            .unwrap_or(String::new())
+// value of an integer literal discriminant, e.g. 3 or -1
+fn discriminant_value(expr: &syn::Expr) -> Option<i128> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(int),
+            ..
+        }) => int.base10_parse().ok(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => discriminant_value(expr).map(|value| -value),
+        syn::Expr::Group(group) => discriminant_value(&group.expr),
+        syn::Expr::Paren(paren) => discriminant_value(&paren.expr),
+        _ => None,
     }
+}
 
-    /*fn field_type(&self) -> FieldType {
-        if let Type::Path(path) = self.ty() {
-            let field_type = &path.path.segments.first().unwrap().ident;
-            let field_type_enum = FieldType::from_str(field_type.to_string().as_str()).expect(
-                format!("Unable to parse {} into FieldType", field_type.to_string()).as_str(),
-            );
-            field_type_enum
-        } else {
-            panic!("Unexpected type: {:?}", self.ty());
-        }
-    }*/
+#[proc_macro_derive(FixedWidthEnum, attributes(fixed_width))]
+pub fn derive_fixed_width_enum(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
 
-    fn ident(&self) -> Option<&Ident> {
-        self.ident.as_ref()
+    match FixedWidthEnumFields::from_derive_input(&input)
+        .and_then(|fw| expand_fixed_width_enum(&fw))
+    {
+        Ok(output) => output.into(),
+        Err(e) => e.write_errors().into(),
     }
+}
 
-    /*fn ty(&self) -> &Type {
-        &self.ty
-    }*/
-}*/
+fn expand_fixed_width_enum(fw: &FixedWidthEnumFields) -> darling::Result<proc_macro2::TokenStream> {
+    let ident = &fw.ident;
+    let ast::Data::Enum(variants) = &fw.data else {
+        return Err(
+            darling::Error::custom("FixedWidthEnum can only be derived for enums").with_span(ident),
+        );
+    };
 
-#[proc_macro_derive(FixedWidthEnum)]
-pub fn derive_fixed_width_enum(input: TokenStream) -> TokenStream {
-    let input: DeriveInput = parse_macro_input!(input);
-    //println!("Derive: {:#?}", input);
-    //let fw: FixedWidthEnumFields = FixedWidthEnumFields::from_derive_input(&input).unwrap();
-    //println!("FWWW: {:#?}", fw);
-
-    let ident = input.ident;
-    if let syn::Data::Enum(enm) = input.data {
-        let mut fields: Vec<proc_macro2::TokenStream> = Vec::new();
-        for variant in enm.variants {
-            let field = FixedWidthEnumField::from_variant(&variant).unwrap();
-            //println!("A: {:#?}", field);
-
-            let field_name = field.ident.to_string();
-            let field_name_ts: proc_macro2::TokenStream = field_name.parse().unwrap();
-
-            let convert = quote! {
-                Self::#field_name_ts => String::from(#field_name),
+    let mut errors = darling::Error::accumulator();
+    let casing = fw.rename_all.as_ref().and_then(|rename_all| {
+        errors.handle(
+            Casing::from_str(rename_all)
+                .map_err(|_| darling::Error::unknown_value(rename_all).with_span(ident)),
+        )
+    });
+
+    let mut keys: Vec<(String, &Ident)> = Vec::new();
+    let mut other: Option<&Ident> = None;
+    let mut discriminant: Option<i128> = None;
+    for variant in variants {
+        let variant_ident = &variant.ident;
+        if variant.other {
+            let holds_string = match variant.fields.fields.as_slice() {
+                [Type::Path(path)] => path
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "String"),
+                _ => false,
             };
+            if !holds_string || variant.key.is_some() || other.is_some() {
+                errors.push(
+                    darling::Error::custom(
+                        "the other variant must be a single Other(String) variant, with no key",
+                    )
+                    .with_span(variant_ident),
+                );
+            }
+            other = Some(variant_ident);
+            continue;
+        }
+        if !variant.fields.is_unit() {
+            errors.push(
+                darling::Error::custom("FixedWidthEnum variants can not have fields, except the #[fixed_width(other)] catch-all")
+                    .with_span(variant_ident),
+            );
+            continue;
+        }
+
+        // implicit discriminants follow the previous one, as in Rust
+        discriminant = match &variant.discriminant {
+            Some(expr) => discriminant_value(expr),
+            None => Some(discriminant.map_or(0, |previous| previous + 1)),
+        };
 
-            fields.push(convert);
+        let key = match (&variant.key, casing) {
+            (Some(key), _) => key.clone(),
+            (None, _) if fw.discriminant_keys => match discriminant {
+                Some(discriminant) => discriminant.to_string(),
+                None => {
+                    errors.push(
+                        darling::Error::custom(
+                            "discriminant_keys needs integer literal discriminants",
+                        )
+                        .with_span(variant_ident),
+                    );
+                    continue;
+                }
+            },
+            (None, Some(casing)) => casing.apply(&variant_ident.to_string()),
+            (None, None) => variant_ident.to_string(),
+        };
+        if keys.iter().any(|(other_key, _)| *other_key == key) {
+            errors.push(
+                darling::Error::custom(format!("duplicate key \"{}\"", key))
+                    .with_span(variant_ident),
+            );
         }
+        keys.push((key, variant_ident));
+    }
+    errors.finish()?;
 
-        let output: proc_macro2::TokenStream = quote! {
-            impl FixedWidthEnum for #ident {
-                fn key(&self) -> String {
-                    match self {
-                        #(#fields)*
-                    }
+    let to_key = keys.iter().map(|(key, variant_ident)| {
+        quote! {
+            Self::#variant_ident => String::from(#key),
+        }
+    });
+    let from_key = keys.iter().map(|(key, variant_ident)| {
+        quote! {
+            #key => Ok(Self::#variant_ident),
+        }
+    });
+    let (other_to_key, other_from_key) = match other {
+        Some(other) => (
+            quote! { Self::#other(key) => key.clone(), },
+            quote! { key => Ok(Self::#other(key.to_string())), },
+        ),
+        None => {
+            let message = format!("Unknown key '{{}}' for {}", ident);
+            (
+                quote! {},
+                quote! { key => Err(fixed_width::error::FixedWidthError::new(format!(#message, key))), },
+            )
+        }
+    };
+
+    Ok(quote! {
+        impl FixedWidthEnum for #ident {
+            fn key(&self) -> String {
+                match self {
+                    #(#to_key)*
+                    #other_to_key
                 }
             }
-        };
+        }
 
-        output.into()
-    } else {
-        panic!("Expect enum, got {:#?}", ident);
-    }
+        impl #ident {
+            /// Returns the variant written as `key`, ignoring the padding spaces around it.
+            pub fn from_key(key: &str) -> Result<Self, fixed_width::error::FixedWidthError> {
+                match key.trim() {
+                    #(#from_key)*
+                    #other_from_key
+                }
+            }
+        }
+
+        impl fixed_width::from_value::FromFieldValue for #ident {
+            fn from_field_value(
+                value: &str,
+                field_config: &fixed_width::model::field_config::FieldConfig,
+            ) -> Result<Self, fixed_width::error::FixedWidthError> {
+                Self::from_key(field_config.unpad(value))
+            }
+        }
+    })
 }
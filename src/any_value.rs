@@ -1,9 +1,10 @@
-use bigdecimal::{num_bigint::Sign, BigDecimal, FromPrimitive, RoundingMode, ToPrimitive};
-use time::format_description;
+use std::io::Write;
+
+use bigdecimal::{num_bigint::Sign, BigDecimal, FromPrimitive, RoundingMode};
 
 use crate::{
     error::{Context, FixedWidthError},
-    model::field_config::FieldConfig,
+    model::{date_encoding::DateEncoding, field_config::FieldConfig},
     FixedWidth, FixedWidthEnum,
 };
 
@@ -14,9 +15,12 @@ pub enum AnyValue {
     TimeDate(time::Date),
     TimeTime(time::Time),
     TimeDateTime(time::PrimitiveDateTime),
+    TimeOffsetDateTime(time::OffsetDateTime),
+    Duration(time::Duration),
     ChronoDate(chrono::NaiveDate),
     ChronoTime(chrono::NaiveTime),
     ChronoDateTime(chrono::NaiveDateTime),
+    ChronoDateTimeOffset(chrono::DateTime<chrono::FixedOffset>),
     Number(AnyNumber),
     Bool(bool),
     Bytes(Vec<u8>),
@@ -32,6 +36,8 @@ pub enum AnyNumber {
     SmallInt(i16),
     Integer(i32),
     BigInteger(i64),
+    HugeInteger(i128),
+    UnsignedHugeInteger(u128),
     Float(f32),
     Real(f64),
     BigDecimal(BigDecimal),
@@ -41,35 +47,119 @@ impl AnyValue {
     //TODO invertire la logica. Tutta la libreria ragiona in byte, non ha senso che qui ragiona in stringhe per poi riconvertirla in byte
     // lasciamo la conversione in byte come ultima operazione, direttamente dentro il trait FixedWidth
 
-    pub fn to_bytes(self, field_config: FieldConfig) -> Result<Vec<u8>, FixedWidthError> {
+    pub fn to_bytes(self, field_config: &FieldConfig) -> Result<Vec<u8>, FixedWidthError> {
         match self {
-            AnyValue::String(s) => Ok(s.as_bytes().to_vec()),
+            AnyValue::String(s) if !field_config.transforms_text() => Ok(s.into_bytes()),
+            AnyValue::Bytes(bytes) if !field_config.transforms_text() => Ok(bytes),
+            any_value => {
+                let mut bytes = Vec::new();
+                any_value.write_bytes(field_config, &mut bytes)?;
+                crate::transform_text(field_config, &mut bytes, 0);
+                Ok(bytes)
+            }
+        }
+    }
+
+    // writes the (unpadded) value at the end of out, without intermediate allocations where possible
+    pub fn write_bytes(
+        &self,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        match self {
+            AnyValue::String(s) => out.extend_from_slice(s.as_bytes()),
             AnyValue::TimeDate(d) => {
-                let format = format_description::parse(field_config.date_format())?;
-                let formatted = d.format(&format)?;
-                Ok(formatted.as_bytes().to_vec())
+                field_config
+                    .date_encoding()
+                    .write_date(*d, field_config, out)?
             }
             AnyValue::TimeTime(t) => {
-                let format = format_description::parse(field_config.time_format())?;
-                let formatted = t.format(&format)?;
-                Ok(formatted.as_bytes().to_vec())
-            }
-            AnyValue::TimeDateTime(dt) => {
-                let format = format_description::parse(field_config.date_time_format())?;
-                let formatted = dt.format(&format)?;
-                Ok(formatted.as_bytes().to_vec())
+                t.format_into(out, field_config.time_format_items()?)?;
             }
-            AnyValue::ChronoDate(d) => {
-                let formatted = d.format(field_config.date_format());
-                Ok(formatted.to_string().as_bytes().to_vec())
+            AnyValue::TimeDateTime(dt) => match field_config.date_encoding() {
+                DateEncoding::Format => {
+                    dt.format_into(out, field_config.date_time_format_items()?)?;
+                }
+                encoding => {
+                    encoding.write_date(dt.date(), field_config, out)?;
+                    dt.time()
+                        .format_into(out, field_config.time_format_items()?)?;
+                }
+            },
+            AnyValue::ChronoDate(d) => match field_config.date_encoding() {
+                DateEncoding::Format => write!(
+                    out,
+                    "{}",
+                    d.format_with_items(field_config.chrono_date_format_items()?.iter())
+                )?,
+                encoding => encoding.write_date(time_date(d)?, field_config, out)?,
+            },
+            AnyValue::ChronoTime(t) => write!(
+                out,
+                "{}",
+                t.format_with_items(field_config.chrono_time_format_items()?.iter())
+            )?,
+            AnyValue::ChronoDateTime(dt) => match field_config.date_encoding() {
+                DateEncoding::Format => write!(
+                    out,
+                    "{}",
+                    dt.format_with_items(field_config.chrono_date_time_format_items()?.iter())
+                )?,
+                encoding => {
+                    encoding.write_date(time_date(&dt.date())?, field_config, out)?;
+                    write!(
+                        out,
+                        "{}",
+                        dt.time()
+                            .format_with_items(field_config.chrono_time_format_items()?.iter())
+                    )?
+                }
+            },
+            AnyValue::TimeOffsetDateTime(dt) => {
+                let dt = match field_config.offset() {
+                    Some(offset) => dt.to_offset(time::UtcOffset::from_whole_seconds(offset)?),
+                    None => *dt,
+                };
+                match field_config.date_encoding() {
+                    DateEncoding::Format => {
+                        dt.format_into(out, field_config.date_time_format_items()?)?;
+                    }
+                    encoding => {
+                        encoding.write_date(dt.date(), field_config, out)?;
+                        dt.time()
+                            .format_into(out, field_config.time_format_items()?)?;
+                    }
+                }
             }
-            AnyValue::ChronoTime(t) => {
-                let formatted = t.format(field_config.time_format());
-                Ok(formatted.to_string().as_bytes().to_vec())
+            AnyValue::Duration(d) => {
+                field_config
+                    .duration_format()
+                    .write_duration(*d, field_config, out)?
             }
-            AnyValue::ChronoDateTime(dt) => {
-                let formatted = dt.format(field_config.date_time_format());
-                Ok(formatted.to_string().as_bytes().to_vec())
+            AnyValue::ChronoDateTimeOffset(dt) => {
+                let dt = match field_config.offset() {
+                    Some(offset) => dt.with_timezone(
+                        &chrono::FixedOffset::east_opt(offset)
+                            .context(format!("Invalid offset of {} seconds", offset))?,
+                    ),
+                    None => *dt,
+                };
+                match field_config.date_encoding() {
+                    DateEncoding::Format => write!(
+                        out,
+                        "{}",
+                        dt.format_with_items(field_config.chrono_date_time_format_items()?.iter())
+                    )?,
+                    encoding => {
+                        encoding.write_date(time_date(&dt.date_naive())?, field_config, out)?;
+                        write!(
+                            out,
+                            "{}",
+                            dt.time()
+                                .format_with_items(field_config.chrono_time_format_items()?.iter())
+                        )?
+                    }
+                }
             }
             AnyValue::Number(n) => match n {
                 /*AnyNumber::SmallInt(si) => {
@@ -87,67 +177,84 @@ impl AnyValue {
                         .context(format!("Unable to convert {} to BigDecimal", bi))?;
                     Self::bigdecimal_to_byte(bd, field_config)
                 }*/
-                AnyNumber::SmallInt(si) => Ok(si.to_string().as_bytes().to_vec()),
-                AnyNumber::Integer(i) => Ok(i.to_string().as_bytes().to_vec()),
-                AnyNumber::BigInteger(bi) => Ok(bi.to_string().as_bytes().to_vec()),
+                AnyNumber::SmallInt(si) => write!(out, "{}", si)?,
+                AnyNumber::Integer(i) => write!(out, "{}", i)?,
+                AnyNumber::BigInteger(bi) => write!(out, "{}", bi)?,
+                AnyNumber::HugeInteger(hi) => write!(out, "{}", hi)?,
+                AnyNumber::UnsignedHugeInteger(uhi) => write!(out, "{}", uhi)?,
                 AnyNumber::Float(f) => {
-                    let bd = BigDecimal::from_f32(f)
+                    let bd = BigDecimal::from_f32(*f)
                         .context(format!("Unable to convert {} to BigDecimal", f))?;
-                    Self::bigdecimal_to_byte(bd, field_config)
+                    Self::write_bigdecimal(&bd, field_config, out)?
                 }
                 AnyNumber::Real(r) => {
-                    let bd = BigDecimal::from_f64(r)
+                    let bd = BigDecimal::from_f64(*r)
                         .context(format!("Unable to convert {} to BigDecimal", r))?;
-                    Self::bigdecimal_to_byte(bd, field_config)
+                    Self::write_bigdecimal(&bd, field_config, out)?
                 }
-                AnyNumber::BigDecimal(bd) => Self::bigdecimal_to_byte(bd, field_config),
+                AnyNumber::BigDecimal(bd) => Self::write_bigdecimal(bd, field_config, out)?,
             },
-            AnyValue::Bytes(bytes) => Ok(bytes),
+            AnyValue::Bytes(bytes) => out.extend_from_slice(bytes),
             AnyValue::Bool(bool) => match bool {
-                true => Ok("1".as_bytes().to_vec()),
-                false => Ok("0".as_bytes().to_vec()),
+                true => out.extend_from_slice(field_config.true_value().as_bytes()),
+                false => out.extend_from_slice(field_config.false_value().as_bytes()),
             },
-            AnyValue::Null(_) => Ok(Vec::new()),
+            AnyValue::Null(_) => Self::write_null(field_config, out),
+        }
+
+        Ok(())
+    }
+
+    // the null sentinel (empty by default), filled up to the field size with null_fill when it is set;
+    // without null_fill the usual padding is added afterwards
+    fn write_null(field_config: &FieldConfig, out: &mut Vec<u8>) {
+        let null = field_config.null().as_bytes();
+        let Some(fill) = field_config.null_fill() else {
+            out.extend_from_slice(null);
+            return;
+        };
+
+        let missing = field_config.size().saturating_sub(null.len());
+        match field_config.pad_left() {
+            true => {
+                out.resize(out.len() + missing, fill);
+                out.extend_from_slice(null);
+            }
+            false => {
+                out.extend_from_slice(null);
+                out.resize(out.len() + missing, fill);
+            }
         }
     }
 
-    fn bigdecimal_to_byte(
-        bd: BigDecimal,
-        field_config: FieldConfig,
-    ) -> Result<Vec<u8>, FixedWidthError> {
+    fn write_bigdecimal(
+        bd: &BigDecimal,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
         let decimals = field_config.decimals();
-        let bd_copy = bd.clone();
         let mut value;
 
         if decimals > 0 {
             let decimals = decimals - 1; // need space to sign
 
-            let value_int = bd
-                .to_i64()
-                .context(format!("Unable to extract integer part of {}", bd))?;
-            let value_decimals = bd - value_int;
-            let value_decimals =
-                value_decimals.with_scale_round(decimals as i64, RoundingMode::HalfUp);
-
-            let value_decimals_str = value_decimals.to_string();
-            let mut value_decimals_str = value_decimals_str[2..].to_string();
-
-            for _ in 0..(decimals - value_decimals_str.len()) {
-                value_decimals_str.push_str("0");
-            }
-
-            //AnyValue::String(bd.to_string())
-            value = format!("{}{}", value_int, value_decimals_str);
+            // rounded to the decimals and written without the point, e.g. 1.25 with 1 decimal is "13"
+            let (digits, _) = bd
+                .with_scale_round(decimals as i64, RoundingMode::HalfUp)
+                .abs()
+                .as_bigint_and_exponent();
+            value = format!("{:0>width$}", digits.to_string(), width = decimals + 1);
         } else {
             value = bd.to_string();
         }
 
-        match bd_copy.sign() {
-            Sign::NoSign | Sign::Plus => value.push_str("+"),
-            Sign::Minus => value.push_str("-"),
+        match bd.sign() {
+            Sign::NoSign | Sign::Plus => value.push('+'),
+            Sign::Minus => value.push('-'),
         };
 
-        Ok(value.as_bytes().to_vec())
+        out.extend_from_slice(value.as_bytes());
+        Ok(())
     }
 
     /*pub fn to_bytes(&self) -> Vec<u8> {
@@ -181,9 +288,35 @@ impl AnyValue {
 }
 
 // Trait for convert a value into AnyValue
+// chrono dates are encoded through time::Date
+fn time_date(date: &chrono::NaiveDate) -> Result<time::Date, FixedWidthError> {
+    use chrono::Datelike;
+    Ok(time::Date::from_ordinal_date(
+        date.year(),
+        date.ordinal() as u16,
+    )?)
+}
+
 pub trait AnyValueTrait: Send + Sync {
+    #[allow(clippy::wrong_self_convention)]
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError>;
+
+    // fast path used by the derive: write the unpadded value straight into the record buffer
+    fn write_value(
+        &self,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        self.into_any_value()?.write_bytes(field_config, out)
+    }
+
+    /// Whether the value is a derived record (or a list of them). A record is written by its own fields,
+    /// so the text transforms and the charset of the field holding it are not applied to its bytes.
+    fn is_record(&self) -> bool {
+        false
+    }
 }
+#[derive(Default)]
 pub struct AnyValueNull {}
 impl AnyValueNull {
     pub fn new() -> AnyValueNull {
@@ -200,221 +333,245 @@ impl AnyValueTrait for &str {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
         Ok(AnyValue::String(self.to_string()))
     }
-}
-impl AnyValueTrait for Option<&str> {
-    fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::String(v.to_string())),
-            None => Ok(AnyValue::Null(None)),
-        }
+    fn write_value(&self, _: &FieldConfig, out: &mut Vec<u8>) -> Result<(), FixedWidthError> {
+        out.extend_from_slice(self.as_bytes());
+        Ok(())
     }
 }
 impl AnyValueTrait for String {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
         Ok(AnyValue::String(self.clone()))
     }
+    fn write_value(&self, _: &FieldConfig, out: &mut Vec<u8>) -> Result<(), FixedWidthError> {
+        out.extend_from_slice(self.as_bytes());
+        Ok(())
+    }
 }
-impl AnyValueTrait for Option<String> {
+impl AnyValueTrait for str {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::String(v.to_string())),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::String(self.to_string()))
+    }
+    fn write_value(&self, _: &FieldConfig, out: &mut Vec<u8>) -> Result<(), FixedWidthError> {
+        out.extend_from_slice(self.as_bytes());
+        Ok(())
     }
 }
-impl AnyValueTrait for u16 {
+impl AnyValueTrait for Box<str> {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::SmallInt(*self as i16)))
+        Ok(AnyValue::String(self.to_string()))
+    }
+    fn write_value(&self, _: &FieldConfig, out: &mut Vec<u8>) -> Result<(), FixedWidthError> {
+        out.extend_from_slice(self.as_bytes());
+        Ok(())
     }
 }
-impl AnyValueTrait for Option<u16> {
+impl AnyValueTrait for std::borrow::Cow<'_, str> {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::SmallInt(*v as i16))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::String(self.to_string()))
+    }
+    fn write_value(&self, _: &FieldConfig, out: &mut Vec<u8>) -> Result<(), FixedWidthError> {
+        out.extend_from_slice(self.as_bytes());
+        Ok(())
     }
 }
-impl AnyValueTrait for i16 {
+impl AnyValueTrait for char {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::SmallInt(*self)))
+        Ok(AnyValue::String(self.to_string()))
+    }
+    fn write_value(&self, _: &FieldConfig, out: &mut Vec<u8>) -> Result<(), FixedWidthError> {
+        out.extend_from_slice(self.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(())
     }
 }
-impl AnyValueTrait for Option<i16> {
+impl AnyValueTrait for std::net::IpAddr {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::SmallInt(*v))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::String(self.to_string()))
     }
 }
-impl AnyValueTrait for i32 {
+impl AnyValueTrait for std::net::Ipv4Addr {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::Integer(*self)))
+        Ok(AnyValue::String(self.to_string()))
     }
 }
-impl AnyValueTrait for Option<i32> {
+impl AnyValueTrait for std::net::Ipv6Addr {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::Integer(*v))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::String(self.to_string()))
     }
 }
-impl AnyValueTrait for u32 {
+#[cfg(feature = "uuid")]
+impl AnyValueTrait for uuid::Uuid {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::Integer(*self as i32)))
+        Ok(AnyValue::String(self.hyphenated().to_string()))
+    }
+    fn write_value(
+        &self,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        let mut buf = uuid::Uuid::encode_buffer();
+        let uuid = match field_config.simple_uuid() {
+            true => self.simple().encode_lower(&mut buf),
+            false => self.hyphenated().encode_lower(&mut buf),
+        };
+        out.extend_from_slice(uuid.as_bytes());
+        Ok(())
     }
 }
-impl AnyValueTrait for Option<u32> {
+// integers are widened to the next signed size, so that e.g. u64::MAX is not written as -1
+impl AnyValueTrait for u8 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::Integer(*v as i32))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::Number(AnyNumber::SmallInt(i16::from(*self))))
     }
 }
-impl AnyValueTrait for f32 {
+impl AnyValueTrait for i8 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::Float(*self)))
+        Ok(AnyValue::Number(AnyNumber::SmallInt(i16::from(*self))))
     }
 }
-impl AnyValueTrait for Option<f32> {
+impl AnyValueTrait for u16 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::Float(*v))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::Number(AnyNumber::Integer(i32::from(*self))))
     }
 }
-impl AnyValueTrait for i64 {
+impl AnyValueTrait for i16 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::BigInteger(*self)))
+        Ok(AnyValue::Number(AnyNumber::SmallInt(*self)))
     }
 }
-impl AnyValueTrait for Option<i64> {
+impl AnyValueTrait for i32 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::BigInteger(*v))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::Number(AnyNumber::Integer(*self)))
+    }
+}
+impl AnyValueTrait for u32 {
+    fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
+        Ok(AnyValue::Number(AnyNumber::BigInteger(i64::from(*self))))
+    }
+}
+impl AnyValueTrait for f32 {
+    fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
+        Ok(AnyValue::Number(AnyNumber::Float(*self)))
+    }
+}
+impl AnyValueTrait for i64 {
+    fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
+        Ok(AnyValue::Number(AnyNumber::BigInteger(*self)))
     }
 }
 impl AnyValueTrait for u64 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::BigInteger(*self as i64)))
+        Ok(AnyValue::Number(AnyNumber::HugeInteger(i128::from(*self))))
     }
 }
-impl AnyValueTrait for Option<u64> {
+impl AnyValueTrait for i128 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::BigInteger(*v as i64))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::Number(AnyNumber::HugeInteger(*self)))
     }
 }
-impl AnyValueTrait for f64 {
+impl AnyValueTrait for u128 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::Real(*self)))
+        Ok(AnyValue::Number(AnyNumber::UnsignedHugeInteger(*self)))
     }
 }
-impl AnyValueTrait for Option<f64> {
+// at most 64 bits on every target Rust supports
+impl AnyValueTrait for isize {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::Real(*v))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::Number(AnyNumber::HugeInteger(*self as i128)))
     }
 }
-impl AnyValueTrait for BigDecimal {
+impl AnyValueTrait for usize {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::Number(AnyNumber::BigDecimal(self.clone())))
+        Ok(AnyValue::Number(AnyNumber::HugeInteger(*self as i128)))
     }
 }
-impl AnyValueTrait for Option<BigDecimal> {
+impl AnyValueTrait for f64 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::Number(AnyNumber::BigDecimal(v.clone()))),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::Number(AnyNumber::Real(*self)))
     }
 }
-impl AnyValueTrait for time::Date {
+impl AnyValueTrait for BigDecimal {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::TimeDate(self.clone()))
+        Ok(AnyValue::Number(AnyNumber::BigDecimal(self.clone())))
+    }
+    fn write_value(
+        &self,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        AnyValue::write_bigdecimal(self, field_config, out)
     }
 }
-impl AnyValueTrait for Option<time::Date> {
+impl AnyValueTrait for time::Date {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::TimeDate(v.clone())),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::TimeDate(*self))
     }
 }
 impl AnyValueTrait for time::Time {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::TimeTime(self.clone()))
+        Ok(AnyValue::TimeTime(*self))
     }
 }
-impl AnyValueTrait for Option<time::Time> {
+impl AnyValueTrait for time::PrimitiveDateTime {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::TimeTime(v.clone())),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::TimeDateTime(*self))
     }
 }
-impl AnyValueTrait for time::PrimitiveDateTime {
+impl AnyValueTrait for time::OffsetDateTime {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::TimeDateTime(self.clone()))
+        Ok(AnyValue::TimeOffsetDateTime(*self))
     }
 }
-impl AnyValueTrait for Option<time::PrimitiveDateTime> {
+impl AnyValueTrait for time::UtcDateTime {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::TimeDateTime(v.clone())),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::TimeOffsetDateTime(
+            self.to_offset(time::UtcOffset::UTC),
+        ))
     }
 }
 impl AnyValueTrait for chrono::NaiveDate {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::ChronoDate(self.clone()))
+        Ok(AnyValue::ChronoDate(*self))
     }
 }
-impl AnyValueTrait for Option<chrono::NaiveDate> {
+impl AnyValueTrait for chrono::NaiveTime {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::ChronoDate(v.clone())),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::ChronoTime(*self))
     }
 }
-impl AnyValueTrait for chrono::NaiveTime {
+impl AnyValueTrait for chrono::NaiveDateTime {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::ChronoTime(self.clone()))
+        Ok(AnyValue::ChronoDateTime(*self))
     }
 }
-impl AnyValueTrait for Option<chrono::NaiveTime> {
+impl AnyValueTrait for time::Duration {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::ChronoTime(v.clone())),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::Duration(*self))
     }
 }
-impl AnyValueTrait for chrono::NaiveDateTime {
+impl AnyValueTrait for std::time::Duration {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::ChronoDateTime(self.clone()))
+        Ok(AnyValue::Duration(
+            time::Duration::try_from(*self).context(format!("Duration {:?} out of range", self))?,
+        ))
     }
 }
-impl AnyValueTrait for Option<chrono::NaiveDateTime> {
+impl AnyValueTrait for chrono::TimeDelta {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::ChronoDateTime(v.clone())),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::Duration(time::Duration::new(
+            self.num_seconds(),
+            self.subsec_nanos(),
+        )))
+    }
+}
+
+// Utc, FixedOffset and Local, all written through their fixed offset
+impl<Tz> AnyValueTrait for chrono::DateTime<Tz>
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: Send + Sync,
+{
+    fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
+        Ok(AnyValue::ChronoDateTimeOffset(self.fixed_offset()))
     }
 }
 impl AnyValueTrait for bool {
@@ -422,34 +579,97 @@ impl AnyValueTrait for bool {
         Ok(AnyValue::Bool(*self))
     }
 }
-impl AnyValueTrait for Option<bool> {
+
+// generic
+// every supported type can be optional, a None is written as the null value of the field
+impl<T> AnyValueTrait for Option<T>
+where
+    T: AnyValueTrait,
+{
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
         match self {
-            Some(v) => Ok(AnyValue::Bool(v.clone())),
+            Some(v) => v.into_any_value(),
             None => Ok(AnyValue::Null(None)),
         }
     }
+    fn write_value(
+        &self,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        match self {
+            Some(v) => v.write_value(field_config, out),
+            None => {
+                AnyValue::write_null(field_config, out);
+                Ok(())
+            }
+        }
+    }
+    fn is_record(&self) -> bool {
+        self.as_ref().is_some_and(T::is_record)
+    }
 }
 
-// generic
-impl<T> AnyValueTrait for T
+/// An `Option<&T>` or `Option<Box<T>>` field seen through `as_deref`, used by the derive: like
+/// `&T` and `Box<T>`, an `Option` of them can not get an impl of its own.
+#[doc(hidden)]
+pub struct OptionRef<'a, T: ?Sized>(pub Option<&'a T>);
+
+impl<T> AnyValueTrait for OptionRef<'_, T>
 where
-    T: FixedWidthEnum,
+    T: AnyValueTrait + ?Sized,
 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        Ok(AnyValue::String(self.key()))
+        match self.0 {
+            Some(v) => v.into_any_value(),
+            None => Ok(AnyValue::Null(None)),
+        }
+    }
+    fn write_value(
+        &self,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        match self.0 {
+            Some(v) => v.write_value(field_config, out),
+            None => {
+                AnyValue::write_null(field_config, out);
+                Ok(())
+            }
+        }
+    }
+    fn is_record(&self) -> bool {
+        self.0.is_some_and(T::is_record)
     }
 }
 
-impl<T> AnyValueTrait for Option<T>
+// (&T and Box<T> can not be covered here, a downstream crate could implement FixedWidthEnum for them:
+// the derive dereferences those fields instead)
+impl<T> AnyValueTrait for std::sync::Arc<T>
+where
+    T: AnyValueTrait + ?Sized,
+{
+    fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
+        (**self).into_any_value()
+    }
+    fn write_value(
+        &self,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        (**self).write_value(field_config, out)
+    }
+    fn is_record(&self) -> bool {
+        (**self).is_record()
+    }
+}
+
+impl<T> AnyValueTrait for T
 where
     T: FixedWidthEnum,
 {
     fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
-        match self {
-            Some(v) => Ok(AnyValue::String(v.key())),
-            None => Ok(AnyValue::Null(None)),
-        }
+        Ok(AnyValue::String(self.key()))
     }
 }
 
@@ -467,6 +687,15 @@ where
 
         Ok(AnyValue::Bytes(bytes))
     }
+    fn write_value(&self, _: &FieldConfig, out: &mut Vec<u8>) -> Result<(), FixedWidthError> {
+        for el in self {
+            el.extend_into(out)?;
+        }
+        Ok(())
+    }
+    fn is_record(&self) -> bool {
+        true
+    }
 }
 
 /*impl<T> AnyValueTrait for Vec<T>
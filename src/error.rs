@@ -1,5 +1,7 @@
 use std::{backtrace::Backtrace, fmt::Display};
 
+use crate::model::validation::Violation;
+
 //#[derive(Debug, Serialize, Deserialize)]
 //#[serde(rename_all = "camelCase")]
 #[derive(Debug, Default)]
@@ -9,6 +11,8 @@ pub struct FixedWidthError {
     backtrace: Option<Backtrace>,
     //#[serde(skip_deserializing, skip_serializing)]
     source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    // every rule broken, when the error comes from a validation
+    violations: Vec<Violation>,
 }
 
 impl FixedWidthError {
@@ -30,8 +34,22 @@ impl FixedWidthError {
     {
         FixedWidthError {
             msg: context.to_string(),
-            backtrace: backtrace,
+            backtrace,
             source: error,
+            violations: Vec::new(),
+        }
+    }
+
+    /// An error listing all the violations, which are also available through [`FixedWidthError::violations`].
+    pub fn from_violations(violations: Vec<Violation>) -> FixedWidthError {
+        let msg = violations
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        FixedWidthError {
+            violations,
+            ..FixedWidthError::new(format!("Validation failed: {}", msg))
         }
     }
 
@@ -65,6 +83,11 @@ impl FixedWidthError {
         self.backtrace.as_ref()
     }
 
+    pub fn violations(&self) -> &[Violation] {
+        self.violations.as_ref()
+    }
+
+    #[allow(clippy::borrowed_box)]
     pub fn source(&self) -> Option<&Box<dyn std::error::Error + Send + Sync>> {
         self.source.as_ref()
     }
@@ -180,3 +203,15 @@ impl From<time::error::Format> for FixedWidthError {
         FixedWidthError::from(error.to_string(), Some(Box::new(error)), None)
     }
 }
+
+impl From<time::error::ComponentRange> for FixedWidthError {
+    fn from(error: time::error::ComponentRange) -> Self {
+        FixedWidthError::from(error.to_string(), Some(Box::new(error)), None)
+    }
+}
+
+impl From<std::io::Error> for FixedWidthError {
+    fn from(error: std::io::Error) -> Self {
+        FixedWidthError::from(error.to_string(), Some(Box::new(error)), None)
+    }
+}
@@ -0,0 +1,152 @@
+use std::str::FromStr;
+
+use bigdecimal::{num_bigint::BigInt, BigDecimal, ToPrimitive};
+
+use crate::{
+    error::{Context, FixedWidthError},
+    model::field_config::FieldConfig,
+};
+
+/// A field value that can be read back from its text in a record, the reverse of
+/// [`AnyValueTrait`](crate::any_value::AnyValueTrait). `value` is the whole field, padding included.
+pub trait FromFieldValue: Sized {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError>;
+}
+
+impl FromFieldValue for String {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        Ok(field_config.unpad(value).to_string())
+    }
+}
+impl FromFieldValue for Box<str> {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        Ok(field_config.unpad(value).into())
+    }
+}
+impl FromFieldValue for char {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        let mut chars = field_config.unpad(value).chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(FixedWidthError::new(format!(
+                "Expected a single char, got '{}' in field '{}'",
+                value,
+                field_config.field_name()
+            ))),
+        }
+    }
+}
+
+// the digits of a number: unlike unpad, a zero pad is stripped too, and a field made only of it is 0
+fn number_text<'a>(value: &'a str, field_config: &FieldConfig) -> &'a str {
+    let pad = field_config.pad() as char;
+    let digits = match field_config.pad_left() {
+        true => value.trim_start_matches(pad),
+        false => value.trim_end_matches(pad),
+    };
+    match digits.is_empty() && pad.is_ascii_digit() {
+        true => "0",
+        false => digits.trim(),
+    }
+}
+
+macro_rules! from_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl FromFieldValue for $ty {
+                fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+                    let digits = number_text(value, field_config);
+                    digits.parse().with_context(|| {
+                        format!("Invalid number '{}' for field '{}'", value, field_config.field_name())
+                    })
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+// decimal numbers are written with the sign after the digits and, with decimals, without the point:
+// decimals counts the sign, e.g. 23.45 with 3 decimals is "2345+"
+impl FromFieldValue for BigDecimal {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        let invalid = || {
+            format!(
+                "Invalid number '{}' for field '{}'",
+                value,
+                field_config.field_name()
+            )
+        };
+        let digits = number_text(value, field_config);
+        let (digits, negative) = match digits.as_bytes().last() {
+            Some(b'+') => (&digits[..digits.len() - 1], false),
+            Some(b'-') => (&digits[..digits.len() - 1], true),
+            _ => (digits, false),
+        };
+
+        let number = match field_config.decimals() {
+            0 => BigDecimal::from_str(digits).with_context(invalid)?,
+            decimals => {
+                // only digits: a BigInt would also take a sign in front of them
+                if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(FixedWidthError::new(invalid()));
+                }
+                let digits = BigInt::from_str(digits).with_context(invalid)?;
+                BigDecimal::new(digits, decimals as i64 - 1)
+            }
+        };
+        Ok(match negative {
+            true => -number,
+            false => number,
+        })
+    }
+}
+impl FromFieldValue for f64 {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        BigDecimal::from_field_value(value, field_config)?
+            .to_f64()
+            .with_context(|| format!("Number '{}' out of range for f64", value))
+    }
+}
+impl FromFieldValue for f32 {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        BigDecimal::from_field_value(value, field_config)?
+            .to_f32()
+            .with_context(|| format!("Number '{}' out of range for f32", value))
+    }
+}
+
+impl FromFieldValue for bool {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        field_config.parse_bool(value)
+    }
+}
+impl FromFieldValue for time::Date {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        field_config.parse_date(value)
+    }
+}
+impl FromFieldValue for chrono::NaiveDate {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        field_config.parse_naive_date(value)
+    }
+}
+impl FromFieldValue for time::Duration {
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        field_config.parse_duration(value)
+    }
+}
+
+// a field holding the null value of the config is None
+impl<T> FromFieldValue for Option<T>
+where
+    T: FromFieldValue,
+{
+    fn from_field_value(value: &str, field_config: &FieldConfig) -> Result<Self, FixedWidthError> {
+        match field_config.is_null(value) {
+            true => Ok(None),
+            false => T::from_field_value(value, field_config).map(Some),
+        }
+    }
+}
@@ -1,9 +1,13 @@
+use std::{borrow::Cow, cell::RefCell, io::Write};
+
 use any_value::AnyValueTrait;
-use error::FixedWidthError;
-use model::field_config::FieldConfig;
+use error::{Context, FixedWidthError};
+use from_value::FromFieldValue;
+use model::{field_config::FieldConfig, text_transform, validation::Violation};
 
 pub mod any_value;
 pub mod error;
+pub mod from_value;
 pub mod model;
 
 // queste 2 linee consentono di caricare la macro FixedWidth allo stesso livello del trait FixedWidth
@@ -11,11 +15,91 @@ extern crate fixed_width_derive;
 pub use fixed_width_derive::FixedWidth;
 pub use fixed_width_derive::FixedWidthEnum;
 
+// used by the code generated by the derive, so records need no direct dependency on time
+#[doc(hidden)]
+pub use time;
+
 pub trait FixedWidth: Send + Sync {
     fn to_fixed_width_bytes(&self) -> Result<Vec<u8>, FixedWidthError>;
     fn to_fixed_width_string(&self) -> Result<String, FixedWidthError> {
         self.to_fixed_width_bytes()
-            .and_then(|bytes| Ok(String::from_utf8(bytes).unwrap()))
+            .map(|bytes| String::from_utf8(bytes).unwrap())
+    }
+
+    /// Appends the record at the end of `buf`, so the same buffer can be reused for many records.
+    /// The derive implements this directly; the default falls back to `to_fixed_width_bytes`.
+    fn extend_into(&self, buf: &mut Vec<u8>) -> Result<(), FixedWidthError> {
+        buf.extend_from_slice(&self.to_fixed_width_bytes()?);
+        Ok(())
+    }
+
+    /// Writes the record to `out`, using a per-thread scratch buffer instead of allocating one per record.
+    fn write_fixed_width(&self, out: &mut impl Write) -> Result<(), FixedWidthError>
+    where
+        Self: Sized,
+    {
+        thread_local! {
+            static BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+        }
+
+        // the buffer is taken out of the cell, so a nested call simply gets a fresh one
+        let mut buf = BUFFER.take();
+        buf.clear();
+        let res = self
+            .extend_into(&mut buf)
+            .and_then(|_| out.write_all(&buf).map_err(Into::into));
+        BUFFER.set(buf);
+
+        res
+    }
+
+    /// Adds the validation rules broken by the fields of the record to `violations`, their paths
+    /// starting with `path`. The derive implements it from the field attributes, e.g. `min` or `pattern`.
+    fn validate_into(&self, path: &str, violations: &mut Vec<Violation>) {
+        let _ = (path, violations);
+    }
+
+    /// Checks every validation rule of the record, the error lists all the rules broken.
+    fn validate(&self) -> Result<(), FixedWidthError> {
+        let mut violations = Vec::new();
+        self.validate_into("", &mut violations);
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(FixedWidthError::from_violations(violations)),
+        }
+    }
+}
+
+/// The layout of a derived record, known without a value. Nested and occurs fields need it, so a
+/// generic record with a nested `T` bounds it as `T: FixedWidthLayout`.
+pub trait FixedWidthLayout: FixedWidth {
+    /// Length in bytes of every record, depends_on groups excluded.
+    const RECORD_LEN: usize;
+
+    /// Appends the blank layout of the record, i.e. what a `None` nested record is written as.
+    fn extend_blank_into(res: &mut Vec<u8>) -> Result<(), FixedWidthError>;
+}
+
+/// A record read back from its fixed width text, derived with `#[fixed_width(parse)]`.
+pub trait FromFixedWidth: Sized {
+    /// Reads a record from the start of `bytes`, returning it with the number of bytes it took.
+    fn read_fixed_width(bytes: &[u8]) -> Result<(Self, usize), FixedWidthError>;
+
+    /// Reads a record that takes exactly `bytes`.
+    fn from_fixed_width_bytes(bytes: &[u8]) -> Result<Self, FixedWidthError> {
+        let (record, len) = Self::read_fixed_width(bytes)?;
+        match len == bytes.len() {
+            true => Ok(record),
+            false => Err(FixedWidthError::new(format!(
+                "Expected a record of {} bytes, got {} instead",
+                len,
+                bytes.len()
+            ))),
+        }
+    }
+
+    fn from_fixed_width_str(s: &str) -> Result<Self, FixedWidthError> {
+        Self::from_fixed_width_bytes(s.as_bytes())
     }
 }
 
@@ -23,6 +107,7 @@ pub trait FixedWidthEnum: Send + Sync {
     fn key(&self) -> String;
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn pad(
     any_value: &dyn AnyValueTrait,
     field_name: &str,
@@ -34,7 +119,6 @@ pub fn pad(
     time_format: &str,
     date_time_format: &str,
 ) -> Result<Vec<u8>, FixedWidthError> {
-    let any_value = any_value.into_any_value()?;
     let field_config = FieldConfig::new(
         field_name,
         size,
@@ -103,23 +187,357 @@ pub fn pad(
     };
     let mut bytes = any_value.to_bytes();*/
 
-    let mut bytes = any_value.to_bytes(field_config)?;
+    let mut bytes = Vec::with_capacity(size);
+    write_field(any_value, &field_config, &mut bytes)?;
 
-    if bytes.len() > size {
-        let len = bytes.len();
-        let value = String::from_utf8(bytes).unwrap_or(String::new());
+    Ok(bytes)
+}
+
+/// Writes a single padded field at the end of `out`.
+///
+/// This is what the derive calls for every field: the value is written straight into the
+/// record buffer and the padding is added around it, so no intermediate `Vec` is needed.
+pub fn write_field<T: AnyValueTrait + ?Sized>(
+    value: &T,
+    field_config: &FieldConfig,
+    out: &mut Vec<u8>,
+) -> Result<(), FixedWidthError> {
+    let write_value = |out: &mut Vec<u8>| value.write_value(field_config, out);
+    match value.is_record() {
+        // a record (e.g. in a Vec or an Option without nested) is written by its own fields
+        true => pad_value(field_config, out, write_value, false),
+        false => write_padded(field_config, out, write_value),
+    }
+}
+
+/// Like [`write_field`], but the unpadded bytes come from a custom serializer,
+/// i.e. a `#[fixed_width(serialize_with = "...")]` function or the `serialize` function of a `with` module.
+pub fn write_field_with<T: ?Sized>(
+    value: &T,
+    field_config: &FieldConfig,
+    out: &mut Vec<u8>,
+    serialize: impl FnOnce(&T, &FieldConfig) -> Result<Vec<u8>, FixedWidthError>,
+) -> Result<(), FixedWidthError> {
+    write_padded(field_config, out, |out| {
+        out.extend_from_slice(&serialize(value, field_config)?);
+        Ok(())
+    })
+}
+
+// applies the text transforms of the config to what was written from start on; bytes that are not
+// UTF-8 (e.g. from a custom serializer) are left as they are
+pub(crate) fn transform_text(field_config: &FieldConfig, out: &mut Vec<u8>, start: usize) {
+    if !field_config.transforms_text() {
+        return;
+    }
+    let Ok(value) = std::str::from_utf8(&out[start..]) else {
+        return;
+    };
+    let len = value.len();
+    let value = match text_transform::transform(value, field_config) {
+        // borrowed and as long as the original, i.e. unchanged
+        Cow::Borrowed(value) if value.len() == len => return,
+        value => value.into_owned(),
+    };
+    out.truncate(start);
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Writes a repeating group of exactly `occurs` records: the missing ones are written by `write_blank`,
+/// more records than slots is an error.
+pub fn write_occurs<T: FixedWidth>(
+    records: &[T],
+    occurs: usize,
+    field_config: &FieldConfig,
+    out: &mut Vec<u8>,
+    write_blank: impl Fn(&mut Vec<u8>) -> Result<(), FixedWidthError>,
+) -> Result<(), FixedWidthError> {
+    write_depending(records, Some(occurs), field_config, out)?;
+    for _ in records.len()..occurs {
+        write_blank(out)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a variable group of records one after the other, with no padding: as many as there are,
+/// up to `max` when the group also has an occurs.
+pub fn write_depending<T: FixedWidth>(
+    records: &[T],
+    max: Option<usize>,
+    field_config: &FieldConfig,
+    out: &mut Vec<u8>,
+) -> Result<(), FixedWidthError> {
+    if let Some(max) = max.filter(|max| records.len() > *max) {
+        return Err(FixedWidthError::new(format!(
+            "Expected at most {} records, got {} instead in field '{}'",
+            max,
+            records.len(),
+            field_config.field_name(),
+        )));
+    }
+
+    for record in records {
+        record.extend_into(out)?;
+    }
+
+    Ok(())
+}
+
+/// Runs `write_value` at the end of `out`, applies the text transforms of the config,
+/// then checks the length and the charset of what it wrote and pads it to the field size.
+///
+/// The derive uses it directly for nested records, whose bytes come from their own `extend_into`.
+pub fn write_padded(
+    field_config: &FieldConfig,
+    out: &mut Vec<u8>,
+    write_value: impl FnOnce(&mut Vec<u8>) -> Result<(), FixedWidthError>,
+) -> Result<(), FixedWidthError> {
+    pad_value(field_config, out, write_value, true)
+}
+
+// write_padded, with the text transforms and the charset check only when `as_text`
+fn pad_value(
+    field_config: &FieldConfig,
+    out: &mut Vec<u8>,
+    write_value: impl FnOnce(&mut Vec<u8>) -> Result<(), FixedWidthError>,
+    as_text: bool,
+) -> Result<(), FixedWidthError> {
+    let start = out.len();
+    write_value(out).inspect_err(|_| out.truncate(start))?;
+    if as_text {
+        transform_text(field_config, out, start);
+    }
+
+    let size = field_config.size();
+    let len = out.len() - start;
+    if len > size {
+        let value = String::from_utf8_lossy(&out[start..]).into_owned();
+        out.truncate(start);
         return Err(FixedWidthError::new(format!(
             "Expected size {}, got {} instead for value '{}' in field '{}'",
-            size, len, value, field_name,
+            size,
+            len,
+            value,
+            field_config.field_name(),
         )));
     }
 
-    for _ in 0..(size - bytes.len()) {
-        match pad_left {
-            true => bytes.insert(0, pad),
-            false => bytes.push(pad),
+    let pad = field_config.pad();
+    let missing = size - len;
+    if let Some(charset) = field_config.charset().filter(|_| as_text) {
+        let column = match field_config.pad_left() {
+            true => missing + 1,
+            false => 1,
+        };
+        let value = String::from_utf8_lossy(&out[start..]);
+        if let Err(e) = charset.check(&value, column, field_config) {
+            out.truncate(start);
+            return Err(e);
         }
     }
 
-    Ok(bytes)
+    match field_config.pad_left() {
+        true => {
+            out.splice(start..start, std::iter::repeat_n(pad, missing));
+        }
+        false => out.resize(out.len() + missing, pad),
+    }
+
+    Ok(())
+}
+
+/// Takes the next `len` bytes of a record being read from `pos` on, moving `pos` after them.
+pub fn read_bytes<'a>(
+    bytes: &'a [u8],
+    pos: &mut usize,
+    len: usize,
+) -> Result<&'a [u8], FixedWidthError> {
+    let start = *pos;
+    let taken = bytes.get(start..start + len).with_context(|| {
+        format!(
+            "Expected {} bytes at position {}, the record ends at {}",
+            len,
+            start,
+            bytes.len()
+        )
+    })?;
+    *pos += len;
+    Ok(taken)
+}
+
+/// Takes a filler or a constant of a record being read, checking it holds the declared bytes.
+pub fn read_literal(bytes: &[u8], pos: &mut usize, literal: &[u8]) -> Result<(), FixedWidthError> {
+    let column = *pos + 1;
+    let taken = read_bytes(bytes, pos, literal.len())?;
+    match taken == literal {
+        true => Ok(()),
+        false => Err(FixedWidthError::new(format!(
+            "Expected '{}' at column {}, got '{}' instead",
+            String::from_utf8_lossy(literal),
+            column,
+            String::from_utf8_lossy(taken)
+        ))),
+    }
+}
+
+// the bytes of the next field, padding included
+fn read_field_bytes<'a>(
+    bytes: &'a [u8],
+    pos: &mut usize,
+    field_config: &FieldConfig,
+) -> Result<&'a [u8], FixedWidthError> {
+    match read_bytes(bytes, pos, field_config.size()) {
+        Ok(field) => Ok(field),
+        Err(e) => Err(FixedWidthError::new(format!(
+            "{} in field '{}'",
+            e.msg(),
+            field_config.field_name()
+        ))),
+    }
+}
+
+/// Takes the text of the next field of a record being read, padding included.
+pub fn read_text<'a>(
+    bytes: &'a [u8],
+    pos: &mut usize,
+    field_config: &FieldConfig,
+) -> Result<&'a str, FixedWidthError> {
+    let field = read_field_bytes(bytes, pos, field_config)?;
+    std::str::from_utf8(field)
+        .with_context(|| format!("Invalid UTF-8 in field '{}'", field_config.field_name()))
+}
+
+/// Reads the next field of a record, the reverse of [`write_field`].
+pub fn read_field<T: FromFieldValue>(
+    bytes: &[u8],
+    pos: &mut usize,
+    field_config: &FieldConfig,
+) -> Result<T, FixedWidthError> {
+    T::from_field_value(read_text(bytes, pos, field_config)?, field_config)
+}
+
+/// Reads the next field of a record through a custom deserializer, given the text without its padding.
+pub fn read_field_with<T>(
+    bytes: &[u8],
+    pos: &mut usize,
+    field_config: &FieldConfig,
+    deserialize: impl FnOnce(&str, &FieldConfig) -> Result<T, FixedWidthError>,
+) -> Result<T, FixedWidthError> {
+    let text = read_text(bytes, pos, field_config)?;
+    deserialize(field_config.unpad(text), field_config)
+}
+
+// the bytes of the records in a field: padded on the left, they sit at the end of it
+fn unpadded_records<'a>(field: &'a [u8], len: usize, field_config: &FieldConfig) -> &'a [u8] {
+    match field_config.pad_left() {
+        true => &field[field.len().saturating_sub(len)..],
+        false => &field[..len.min(field.len())],
+    }
+}
+
+fn is_blank<T: FixedWidthLayout>(record: &[u8]) -> Result<bool, FixedWidthError> {
+    let mut blank = Vec::with_capacity(T::RECORD_LEN);
+    T::extend_blank_into(&mut blank)?;
+    Ok(record == blank)
+}
+
+/// Reads a nested record from the next field of a record.
+pub fn read_nested<T: FromFixedWidth + FixedWidthLayout>(
+    bytes: &[u8],
+    pos: &mut usize,
+    field_config: &FieldConfig,
+) -> Result<T, FixedWidthError> {
+    let field = read_field_bytes(bytes, pos, field_config)?;
+    T::from_fixed_width_bytes(unpadded_records(field, T::RECORD_LEN, field_config))
+}
+
+/// Like [`read_nested`], `None` when the field holds the blank layout of the record.
+pub fn read_optional_nested<T: FromFixedWidth + FixedWidthLayout>(
+    bytes: &[u8],
+    pos: &mut usize,
+    field_config: &FieldConfig,
+) -> Result<Option<T>, FixedWidthError> {
+    let field = read_field_bytes(bytes, pos, field_config)?;
+    let record = unpadded_records(field, T::RECORD_LEN, field_config);
+    match is_blank::<T>(record)? {
+        true => Ok(None),
+        false => T::from_fixed_width_bytes(record).map(Some),
+    }
+}
+
+// every one of the `occurs` slots of the next field, blank ones included
+fn read_slots<T: FromFixedWidth + FixedWidthLayout>(
+    bytes: &[u8],
+    pos: &mut usize,
+    occurs: usize,
+    field_config: &FieldConfig,
+) -> Result<Vec<(T, bool)>, FixedWidthError> {
+    let field = read_field_bytes(bytes, pos, field_config)?;
+    let records = unpadded_records(field, occurs * T::RECORD_LEN, field_config);
+    records
+        .chunks(T::RECORD_LEN.max(1))
+        .take(occurs)
+        .map(|record| Ok((T::from_fixed_width_bytes(record)?, is_blank::<T>(record)?)))
+        .collect()
+}
+
+/// Reads a repeating group of `occurs` records, the reverse of [`write_occurs`]:
+/// the blank slots after the last record are dropped.
+pub fn read_occurs<T: FromFixedWidth + FixedWidthLayout>(
+    bytes: &[u8],
+    pos: &mut usize,
+    occurs: usize,
+    field_config: &FieldConfig,
+) -> Result<Vec<T>, FixedWidthError> {
+    let mut slots = read_slots::<T>(bytes, pos, occurs, field_config)?;
+    while slots.last().is_some_and(|(_, blank)| *blank) {
+        slots.pop();
+    }
+
+    Ok(slots.into_iter().map(|(record, _)| record).collect())
+}
+
+/// Like [`read_occurs`] for a `[T; N]` field, which always holds N records.
+pub fn read_occurs_array<T: FromFixedWidth + FixedWidthLayout, const N: usize>(
+    bytes: &[u8],
+    pos: &mut usize,
+    field_config: &FieldConfig,
+) -> Result<[T; N], FixedWidthError> {
+    let slots = read_slots::<T>(bytes, pos, N, field_config)?;
+    let records: Vec<T> = slots.into_iter().map(|(record, _)| record).collect();
+    records.try_into().ok().with_context(|| {
+        format!(
+            "Expected {} records in field '{}'",
+            N,
+            field_config.field_name()
+        )
+    })
+}
+
+/// Reads a variable group of `count` records one after the other, the reverse of [`write_depending`].
+pub fn read_depending<T: FromFixedWidth>(
+    bytes: &[u8],
+    pos: &mut usize,
+    count: usize,
+    max: Option<usize>,
+    field_config: &FieldConfig,
+) -> Result<Vec<T>, FixedWidthError> {
+    if let Some(max) = max.filter(|max| count > *max) {
+        return Err(FixedWidthError::new(format!(
+            "Expected at most {} records, got a count of {} instead in field '{}'",
+            max,
+            count,
+            field_config.field_name(),
+        )));
+    }
+
+    let mut records = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (record, len) = T::read_fixed_width(bytes.get(*pos..).unwrap_or_default())?;
+        *pos += len;
+        records.push(record);
+    }
+
+    Ok(records)
 }
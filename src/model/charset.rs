@@ -0,0 +1,72 @@
+use crate::error::FixedWidthError;
+
+use super::field_config::FieldConfig;
+
+/// Characters allowed in a field, selected with `#[fixed_width(charset = "...")]`.
+/// The padding is not checked, only the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Charset {
+    /// ASCII digits
+    Numeric,
+    /// ASCII letters and space
+    Alpha,
+    /// ASCII letters, digits and space
+    Alnum,
+    /// from space to '~'
+    AsciiPrintable,
+    /// inclusive ranges of chars, e.g. `[A-Z0-9 ]` is `[('A', 'Z'), ('0', '9'), (' ', ' ')]`
+    Set(Vec<(char, char)>),
+}
+
+impl Charset {
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Charset::Numeric => c.is_ascii_digit(),
+            Charset::Alpha => c.is_ascii_alphabetic() || c == ' ',
+            Charset::Alnum => c.is_ascii_alphanumeric() || c == ' ',
+            Charset::AsciiPrintable => (' '..='~').contains(&c),
+            Charset::Set(ranges) => ranges.iter().any(|(from, to)| (*from..=*to).contains(&c)),
+        }
+    }
+
+    // name of the charset in the error messages, a set is written back in the [A-Z0-9 ] form
+    fn describe(&self) -> String {
+        match self {
+            Charset::Numeric => "numeric".to_string(),
+            Charset::Alpha => "alpha".to_string(),
+            Charset::Alnum => "alnum".to_string(),
+            Charset::AsciiPrintable => "ascii_printable".to_string(),
+            Charset::Set(ranges) => {
+                let mut set = String::from("[");
+                for (from, to) in ranges {
+                    set.push(*from);
+                    if from != to {
+                        set.push('-');
+                        set.push(*to);
+                    }
+                }
+                set.push(']');
+                set
+            }
+        }
+    }
+
+    // `column` is the 1-based position of the first char of value inside the field
+    pub(crate) fn check(
+        &self,
+        value: &str,
+        column: usize,
+        field_config: &FieldConfig,
+    ) -> Result<(), FixedWidthError> {
+        match value.chars().enumerate().find(|(_, c)| !self.contains(*c)) {
+            Some((position, c)) => Err(FixedWidthError::new(format!(
+                "Invalid character '{}' at position {} of field '{}', expected {}",
+                c.escape_debug(),
+                column + position,
+                field_config.field_name(),
+                self.describe()
+            ))),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::io::Write;
+
+use time::{Date, Duration};
+
+use crate::error::{Context, FixedWidthError};
+
+use super::field_config::FieldConfig;
+
+/// How a date is encoded, selected with `#[fixed_width(date_encoding = "...")]`.
+/// Datetimes are written as the encoded date followed by the time in `time_format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateEncoding {
+    /// `date_format` (or `date_time_format` for datetimes)
+    #[default]
+    Format,
+    /// YYYYDDD, the day of the year after the year
+    Ordinal,
+    /// YYDDD, with the century resolved through the pivot year
+    ShortOrdinal,
+    /// YYMMDD, with the century resolved through the pivot year
+    ShortYear,
+    /// number of days since the epoch
+    EpochDays,
+}
+
+impl DateEncoding {
+    pub(crate) fn write_date(
+        self,
+        date: Date,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        let short_year = date.year().rem_euclid(100);
+        // a two-digit year has to read back as the same year
+        if matches!(self, DateEncoding::ShortOrdinal | DateEncoding::ShortYear)
+            && field_config.full_year(short_year) != date.year()
+        {
+            return Err(FixedWidthError::new(format!(
+                "Date {} is outside the two-digit years of field '{}' (pivot {})",
+                date,
+                field_config.field_name(),
+                field_config.pivot()
+            )));
+        }
+        match self {
+            DateEncoding::Format => {
+                date.format_into(out, field_config.date_format_items()?)?;
+            }
+            DateEncoding::Ordinal => write!(out, "{:04}{:03}", date.year(), date.ordinal())?,
+            DateEncoding::ShortOrdinal => write!(out, "{:02}{:03}", short_year, date.ordinal())?,
+            DateEncoding::ShortYear => write!(
+                out,
+                "{:02}{:02}{:02}",
+                short_year,
+                u8::from(date.month()),
+                date.day()
+            )?,
+            DateEncoding::EpochDays => {
+                write!(out, "{}", (date - field_config.epoch()).whole_days())?
+            }
+        };
+        Ok(())
+    }
+
+    pub(crate) fn parse_date(
+        self,
+        value: &str,
+        field_config: &FieldConfig,
+    ) -> Result<Date, FixedWidthError> {
+        let invalid = || {
+            format!(
+                "Invalid date '{}' for field '{}'",
+                value,
+                field_config.field_name()
+            )
+        };
+        let number = |range: std::ops::Range<usize>| -> Result<i32, FixedWidthError> {
+            value
+                .get(range)
+                .and_then(|digits| digits.parse().ok())
+                .with_context(invalid)
+        };
+
+        let date = match self {
+            DateEncoding::Format => Date::parse(value, field_config.date_format_items()?).ok(),
+            DateEncoding::Ordinal if value.len() == 7 => {
+                Date::from_ordinal_date(number(0..4)?, number(4..7)? as u16).ok()
+            }
+            DateEncoding::ShortOrdinal if value.len() == 5 => {
+                let year = field_config.full_year(number(0..2)?);
+                Date::from_ordinal_date(year, number(2..5)? as u16).ok()
+            }
+            DateEncoding::ShortYear if value.len() == 6 => {
+                let year = field_config.full_year(number(0..2)?);
+                let (month, day) = (number(2..4)?, number(4..6)?);
+                time::Month::try_from(month as u8)
+                    .ok()
+                    .and_then(|month| Date::from_calendar_date(year, month, day as u8).ok())
+            }
+            DateEncoding::EpochDays => {
+                let days: i64 = value.parse().ok().with_context(invalid)?;
+                field_config.epoch().checked_add(Duration::days(days))
+            }
+            _ => None,
+        };
+        date.with_context(invalid)
+    }
+}
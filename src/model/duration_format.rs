@@ -0,0 +1,119 @@
+use std::io::Write;
+
+use time::Duration;
+
+use crate::error::{Context, FixedWidthError};
+
+use super::field_config::FieldConfig;
+
+/// How a duration is written, selected with `#[fixed_width(duration_format = "...")]`.
+/// Totals are scaled by `decimals`, e.g. 90 seconds as minutes with 2 decimals is "150".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DurationFormat {
+    #[default]
+    Seconds,
+    Milliseconds,
+    Minutes,
+    Hours,
+    /// hours, minutes and seconds, hours going beyond 24 (and 99) as needed
+    HhMmSs,
+    /// hours and minutes, hours going beyond 24 (and 99) as needed
+    HhMm,
+}
+
+impl DurationFormat {
+    // length of one unit of a total, the components are whole seconds
+    fn unit_nanos(self) -> i128 {
+        match self {
+            DurationFormat::Milliseconds => 1_000_000,
+            DurationFormat::Minutes => 60_000_000_000,
+            DurationFormat::Hours => 3_600_000_000_000,
+            DurationFormat::Seconds | DurationFormat::HhMmSs | DurationFormat::HhMm => {
+                1_000_000_000
+            }
+        }
+    }
+
+    pub(crate) fn write_duration(
+        self,
+        duration: Duration,
+        field_config: &FieldConfig,
+        out: &mut Vec<u8>,
+    ) -> Result<(), FixedWidthError> {
+        let sign = if duration.is_negative() { "-" } else { "" };
+        match self {
+            DurationFormat::HhMmSs | DurationFormat::HhMm => {
+                let seconds = duration.whole_seconds().unsigned_abs();
+                let (hours, minutes) = (seconds / 3600, seconds % 3600 / 60);
+                match self {
+                    DurationFormat::HhMmSs => {
+                        write!(out, "{}{:02}{:02}{:02}", sign, hours, minutes, seconds % 60)?
+                    }
+                    _ => write!(out, "{}{:02}{:02}", sign, hours, minutes)?,
+                }
+            }
+            _ => {
+                // rounded half up to the last decimal
+                let scale = 10i128.pow(field_config.decimals() as u32);
+                let nanos = duration.whole_nanoseconds().unsigned_abs() * scale as u128;
+                let unit = self.unit_nanos() as u128;
+                let total = (nanos + unit / 2) / unit;
+                write!(out, "{}{}", sign, total)?
+            }
+        };
+        Ok(())
+    }
+
+    pub(crate) fn parse_duration(
+        self,
+        value: &str,
+        field_config: &FieldConfig,
+    ) -> Result<Duration, FixedWidthError> {
+        let invalid = || {
+            format!(
+                "Invalid duration '{}' for field '{}'",
+                value,
+                field_config.field_name()
+            )
+        };
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(FixedWidthError::new(invalid()));
+        }
+
+        let nanos = match self {
+            DurationFormat::HhMmSs | DurationFormat::HhMm => {
+                let minutes_at = digits.len().checked_sub(match self {
+                    DurationFormat::HhMmSs => 4,
+                    _ => 2,
+                });
+                let minutes_at = minutes_at.filter(|at| *at > 0).with_context(invalid)?;
+                let hours: i128 = digits[..minutes_at].parse().ok().with_context(invalid)?;
+                let minutes: i128 = digits[minutes_at..minutes_at + 2].parse().unwrap_or(0);
+                let seconds: i128 = digits
+                    .get(minutes_at + 2..)
+                    .unwrap_or("")
+                    .parse()
+                    .unwrap_or(0);
+                if minutes > 59 || seconds > 59 {
+                    return Err(FixedWidthError::new(invalid()));
+                }
+                (hours * 3600 + minutes * 60 + seconds) * 1_000_000_000
+            }
+            _ => {
+                let total: i128 = digits.parse().ok().with_context(invalid)?;
+                let scale = 10i128.pow(field_config.decimals() as u32);
+                total.checked_mul(self.unit_nanos()).with_context(invalid)? / scale
+            }
+        };
+
+        let nanos = if negative { -nanos } else { nanos };
+        let seconds = i64::try_from(nanos / 1_000_000_000)
+            .ok()
+            .with_context(invalid)?;
+        Ok(Duration::new(seconds, (nanos % 1_000_000_000) as i32))
+    }
+}
@@ -1,3 +1,29 @@
+use std::sync::OnceLock;
+
+use bigdecimal::BigDecimal;
+use chrono::format::{Item, StrftimeItems};
+use regex::Regex;
+use time::{
+    error::InvalidFormatDescription,
+    format_description::{self, OwnedFormatItem},
+};
+
+use crate::{
+    any_value::AnyValueTrait,
+    error::{Context, FixedWidthError},
+};
+
+use super::{
+    charset::Charset,
+    date_encoding::DateEncoding,
+    duration_format::DurationFormat,
+    text_transform::TextCase,
+    validation::{self, Violation},
+};
+
+type FormatItems = OnceLock<Result<OwnedFormatItem, InvalidFormatDescription>>;
+type ChronoItems = OnceLock<Result<Vec<Item<'static>>, chrono::format::ParseError>>;
+
 pub struct FieldConfig {
     field_name: String,
     size: usize,
@@ -7,9 +33,47 @@ pub struct FieldConfig {
     date_format: String, //TODO require field when a Date is passed
     time_format: String,
     date_time_format: String,
+    // how a bool is written, "1"/"0" unless set with with_bool
+    true_value: String,
+    false_value: String,
+    // how a None is written: the null sentinel, filled with null_fill (or padded as usual)
+    null: String,
+    null_fill: Option<u8>,
+    // offset (in seconds east of UTC) datetimes with an offset are converted to before formatting
+    offset: Option<i32>,
+    date_encoding: DateEncoding,
+    // two-digit years from the pivot on are 19xx, the ones below are 20xx
+    pivot: i32,
+    epoch: time::Date,
+    duration_format: DurationFormat,
+    // uuids without hyphens
+    simple_uuid: bool,
+    // text transforms, applied to the value before it is padded
+    case: Option<TextCase>,
+    trim: bool,
+    collapse_whitespace: bool,
+    transliterate: bool,
+    // chars allowed in the value, checked after the text transforms
+    charset: Option<Charset>,
+    // validation rules, checked by validate
+    min: Option<BigDecimal>,
+    max: Option<BigDecimal>,
+    pattern: Option<String>,
+    required: bool,
+    one_of: Vec<String>,
+    pattern_regex: OnceLock<Result<Regex, regex::Error>>,
+    // parsed lazily on first use and then reused for every record
+    date_format_items: FormatItems,
+    time_format_items: FormatItems,
+    date_time_format_items: FormatItems,
+    // the same formats as strftime items, for chrono
+    chrono_date_format_items: ChronoItems,
+    chrono_time_format_items: ChronoItems,
+    chrono_date_time_format_items: ChronoItems,
 }
 
 impl FieldConfig {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         field_name: &str,
         size: usize,
@@ -29,9 +93,153 @@ impl FieldConfig {
             date_format: date_format.to_string(),
             time_format: time_format.to_string(),
             date_time_format: date_time_format.to_string(),
+            true_value: "1".to_string(),
+            false_value: "0".to_string(),
+            null: String::new(),
+            null_fill: None,
+            offset: None,
+            date_encoding: DateEncoding::Format,
+            pivot: 50,
+            epoch: time::macros::date!(1970 - 01 - 01),
+            duration_format: DurationFormat::Seconds,
+            simple_uuid: false,
+            case: None,
+            trim: false,
+            collapse_whitespace: false,
+            transliterate: false,
+            charset: None,
+            min: None,
+            max: None,
+            pattern: None,
+            required: false,
+            one_of: Vec::new(),
+            pattern_regex: OnceLock::new(),
+            date_format_items: OnceLock::new(),
+            time_format_items: OnceLock::new(),
+            date_time_format_items: OnceLock::new(),
+            chrono_date_format_items: OnceLock::new(),
+            chrono_time_format_items: OnceLock::new(),
+            chrono_date_time_format_items: OnceLock::new(),
         }
     }
 
+    /// Sets how `true` and `false` are written, e.g. "S"/"N" or "X"/"".
+    pub fn with_bool(mut self, true_value: &str, false_value: &str) -> FieldConfig {
+        self.true_value = true_value.to_string();
+        self.false_value = false_value.to_string();
+        self
+    }
+
+    /// Sets the value written for `None`, e.g. "N/A".
+    pub fn with_null(mut self, null: &str) -> FieldConfig {
+        self.null = null.to_string();
+        self
+    }
+
+    /// Sets the char that fills a `None` up to the field size instead of the pad, e.g. b'0' for "00000000".
+    pub fn with_null_fill(mut self, null_fill: u8) -> FieldConfig {
+        self.null_fill = Some(null_fill);
+        self
+    }
+
+    /// Converts datetimes with an offset (e.g. `OffsetDateTime` or chrono `DateTime<Utc>`) to this offset,
+    /// in seconds east of UTC, before they are formatted.
+    pub fn with_offset(mut self, offset: i32) -> FieldConfig {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets how dates are encoded, `date_format` by default.
+    pub fn with_date_encoding(mut self, date_encoding: DateEncoding) -> FieldConfig {
+        self.date_encoding = date_encoding;
+        self
+    }
+
+    /// Sets the pivot of two-digit years: with the default of 50, "50" is 1950 and "49" is 2049.
+    pub fn with_pivot(mut self, pivot: i32) -> FieldConfig {
+        self.pivot = pivot;
+        self
+    }
+
+    /// Sets the epoch of the `EpochDays` encoding, 1970-01-01 by default.
+    pub fn with_epoch(mut self, epoch: time::Date) -> FieldConfig {
+        self.epoch = epoch;
+        self
+    }
+
+    /// Sets how durations are written, total seconds by default.
+    pub fn with_duration_format(mut self, duration_format: DurationFormat) -> FieldConfig {
+        self.duration_format = duration_format;
+        self
+    }
+
+    /// Writes uuids in the simple form, without hyphens, instead of the hyphenated one.
+    pub fn with_simple_uuid(mut self, simple_uuid: bool) -> FieldConfig {
+        self.simple_uuid = simple_uuid;
+        self
+    }
+
+    /// Converts the value to upper or lower case before it is padded.
+    pub fn with_case(mut self, case: TextCase) -> FieldConfig {
+        self.case = Some(case);
+        self
+    }
+
+    /// Strips leading and trailing whitespace from the value before it is padded.
+    pub fn with_trim(mut self, trim: bool) -> FieldConfig {
+        self.trim = trim;
+        self
+    }
+
+    /// Replaces every run of whitespace in the value with a single space.
+    pub fn with_collapse_whitespace(mut self, collapse_whitespace: bool) -> FieldConfig {
+        self.collapse_whitespace = collapse_whitespace;
+        self
+    }
+
+    /// Replaces accented and special characters with ASCII, see [`crate::model::text_transform::transliterate`].
+    pub fn with_transliterate(mut self, transliterate: bool) -> FieldConfig {
+        self.transliterate = transliterate;
+        self
+    }
+
+    /// Restricts the chars of the value to a charset: writing (and [`FieldConfig::check_charset`])
+    /// fails on the first char outside of it.
+    pub fn with_charset(mut self, charset: Charset) -> FieldConfig {
+        self.charset = Some(charset);
+        self
+    }
+
+    /// Sets the lowest number the field can hold, checked by [`FieldConfig::validate`].
+    pub fn with_min(mut self, min: BigDecimal) -> FieldConfig {
+        self.min = Some(min);
+        self
+    }
+
+    /// Sets the highest number the field can hold, checked by [`FieldConfig::validate`].
+    pub fn with_max(mut self, max: BigDecimal) -> FieldConfig {
+        self.max = Some(max);
+        self
+    }
+
+    /// Sets a regex the written value has to match, e.g. `^\d{5}$`, checked by [`FieldConfig::validate`].
+    pub fn with_pattern(mut self, pattern: &str) -> FieldConfig {
+        self.pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// Makes `None` and blank values a violation for [`FieldConfig::validate`].
+    pub fn with_required(mut self, required: bool) -> FieldConfig {
+        self.required = required;
+        self
+    }
+
+    /// Restricts the written value to a list, checked by [`FieldConfig::validate`].
+    pub fn with_one_of(mut self, one_of: &[&str]) -> FieldConfig {
+        self.one_of = one_of.iter().map(|value| value.to_string()).collect();
+        self
+    }
+
     pub fn field_name(&self) -> &str {
         self.field_name.as_ref()
     }
@@ -63,4 +271,234 @@ impl FieldConfig {
     pub fn date_time_format(&self) -> &str {
         self.date_time_format.as_ref()
     }
+
+    pub fn true_value(&self) -> &str {
+        self.true_value.as_ref()
+    }
+
+    pub fn false_value(&self) -> &str {
+        self.false_value.as_ref()
+    }
+
+    pub fn null(&self) -> &str {
+        self.null.as_ref()
+    }
+
+    pub fn null_fill(&self) -> Option<u8> {
+        self.null_fill
+    }
+
+    pub fn offset(&self) -> Option<i32> {
+        self.offset
+    }
+
+    pub fn date_encoding(&self) -> DateEncoding {
+        self.date_encoding
+    }
+
+    pub fn pivot(&self) -> i32 {
+        self.pivot
+    }
+
+    pub fn epoch(&self) -> time::Date {
+        self.epoch
+    }
+
+    /// Resolves a two-digit year with the pivot.
+    pub fn full_year(&self, short_year: i32) -> i32 {
+        match short_year >= self.pivot {
+            true => 1900 + short_year,
+            false => 2000 + short_year,
+        }
+    }
+
+    /// Reads back a date written with this config, ignoring the padding.
+    pub fn parse_date(&self, value: &str) -> Result<time::Date, FixedWidthError> {
+        self.date_encoding.parse_date(self.unpad(value), self)
+    }
+
+    /// Like [`FieldConfig::parse_date`], for chrono: with the `Format` encoding `date_format` is a strftime format.
+    pub fn parse_naive_date(&self, value: &str) -> Result<chrono::NaiveDate, FixedWidthError> {
+        if self.date_encoding == DateEncoding::Format {
+            let value = self.unpad(value);
+            return chrono::NaiveDate::parse_from_str(value, &self.date_format).with_context(
+                || format!("Invalid date '{}' for field '{}'", value, self.field_name),
+            );
+        }
+        let date = self.parse_date(value)?;
+        chrono::NaiveDate::from_yo_opt(date.year(), date.ordinal().into())
+            .ok_or_else(|| FixedWidthError::new(format!("Date {} out of range for chrono", date)))
+    }
+
+    pub fn duration_format(&self) -> DurationFormat {
+        self.duration_format
+    }
+
+    pub fn simple_uuid(&self) -> bool {
+        self.simple_uuid
+    }
+
+    pub fn case(&self) -> Option<TextCase> {
+        self.case
+    }
+
+    pub fn trim(&self) -> bool {
+        self.trim
+    }
+
+    pub fn collapse_whitespace(&self) -> bool {
+        self.collapse_whitespace
+    }
+
+    pub fn transliterate(&self) -> bool {
+        self.transliterate
+    }
+
+    pub fn charset(&self) -> Option<&Charset> {
+        self.charset.as_ref()
+    }
+
+    /// Checks a value read from a record against the charset, ignoring the padding.
+    /// The position in the error counts from the first char of the field.
+    pub fn check_charset(&self, value: &str) -> Result<(), FixedWidthError> {
+        let Some(charset) = &self.charset else {
+            return Ok(());
+        };
+        let unpadded = self.unpad(value);
+        let column = match self.pad_left {
+            true => value[..value.len() - unpadded.len()].chars().count() + 1,
+            false => 1,
+        };
+        charset.check(unpadded, column, self)
+    }
+
+    pub fn min(&self) -> Option<&BigDecimal> {
+        self.min.as_ref()
+    }
+
+    pub fn max(&self) -> Option<&BigDecimal> {
+        self.max.as_ref()
+    }
+
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    pub fn required(&self) -> bool {
+        self.required
+    }
+
+    pub fn one_of(&self) -> &[String] {
+        self.one_of.as_ref()
+    }
+
+    // compiled on first use, like the date/time formats
+    pub(crate) fn pattern_regex(&self) -> Result<&Regex, &regex::Error> {
+        self.pattern_regex
+            .get_or_init(|| Regex::new(self.pattern.as_deref().unwrap_or_default()))
+            .as_ref()
+    }
+
+    /// Checks a value against the validation rules of the field and adds the broken ones to `violations`,
+    /// with the given path. A value read back can be checked as a `&str`: min and max parse it as a number.
+    pub fn validate<T: AnyValueTrait + ?Sized>(
+        &self,
+        value: &T,
+        path: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        validation::validate(self, value, path, violations)
+    }
+
+    // whether the written value has to go through text_transform::transform
+    pub(crate) fn transforms_text(&self) -> bool {
+        self.case.is_some() || self.trim || self.collapse_whitespace || self.transliterate
+    }
+
+    /// Reads back a duration written with this config, ignoring the padding.
+    pub fn parse_duration(&self, value: &str) -> Result<time::Duration, FixedWidthError> {
+        self.duration_format.parse_duration(self.unpad(value), self)
+    }
+
+    /// Strips the padding from a value read back; a digit pad is kept, leading zeros are part of numbers.
+    pub fn unpad<'a>(&self, value: &'a str) -> &'a str {
+        if self.pad.is_ascii_digit() {
+            return value;
+        }
+        match self.pad_left {
+            true => value.trim_start_matches(self.pad as char),
+            false => value.trim_end_matches(self.pad as char),
+        }
+    }
+
+    /// Tells whether a value read from a record is a `None` written with this config.
+    pub fn is_null(&self, value: &str) -> bool {
+        let fill = self.null_fill.unwrap_or(self.pad) as char;
+        value.trim_matches(fill) == self.null.trim_matches(fill)
+    }
+
+    /// Reads back a bool written with this config: the padding is ignored and the match is case-insensitive.
+    pub fn parse_bool(&self, value: &str) -> Result<bool, FixedWidthError> {
+        let trimmed = value.trim_matches(self.pad as char);
+        if trimmed.eq_ignore_ascii_case(self.true_value.trim_matches(self.pad as char)) {
+            Ok(true)
+        } else if trimmed.eq_ignore_ascii_case(self.false_value.trim_matches(self.pad as char)) {
+            Ok(false)
+        } else {
+            Err(FixedWidthError::new(format!(
+                "Unrecognised value '{}' for bool field '{}', expected '{}' or '{}'",
+                value, self.field_name, self.true_value, self.false_value
+            )))
+        }
+    }
+
+    /// Parsed `date_format`, used to format `time::Date` values.
+    pub fn date_format_items(&self) -> Result<&OwnedFormatItem, FixedWidthError> {
+        Self::format_items(&self.date_format_items, &self.date_format)
+    }
+
+    /// Parsed `time_format`, used to format `time::Time` values.
+    pub fn time_format_items(&self) -> Result<&OwnedFormatItem, FixedWidthError> {
+        Self::format_items(&self.time_format_items, &self.time_format)
+    }
+
+    /// Parsed `date_time_format`, used to format `time::PrimitiveDateTime` values.
+    pub fn date_time_format_items(&self) -> Result<&OwnedFormatItem, FixedWidthError> {
+        Self::format_items(&self.date_time_format_items, &self.date_time_format)
+    }
+
+    /// `date_format` as strftime items, used to format chrono dates.
+    pub fn chrono_date_format_items(&self) -> Result<&[Item<'static>], FixedWidthError> {
+        Self::chrono_items(&self.chrono_date_format_items, &self.date_format)
+    }
+
+    /// `time_format` as strftime items, used to format chrono times.
+    pub fn chrono_time_format_items(&self) -> Result<&[Item<'static>], FixedWidthError> {
+        Self::chrono_items(&self.chrono_time_format_items, &self.time_format)
+    }
+
+    /// `date_time_format` as strftime items, used to format chrono datetimes.
+    pub fn chrono_date_time_format_items(&self) -> Result<&[Item<'static>], FixedWidthError> {
+        Self::chrono_items(&self.chrono_date_time_format_items, &self.date_time_format)
+    }
+
+    fn chrono_items<'a>(
+        items: &'a ChronoItems,
+        format: &str,
+    ) -> Result<&'a [Item<'static>], FixedWidthError> {
+        items
+            .get_or_init(|| StrftimeItems::new(format).parse_to_owned())
+            .as_deref()
+            .map_err(|e| FixedWidthError::new(format!("Invalid format '{}': {}", format, e)))
+    }
+
+    fn format_items<'a>(
+        items: &'a FormatItems,
+        format: &str,
+    ) -> Result<&'a OwnedFormatItem, FixedWidthError> {
+        items
+            .get_or_init(|| format_description::parse_owned::<1>(format))
+            .as_ref()
+            .map_err(|e| e.clone().into())
+    }
 }
@@ -1 +1,6 @@
+pub mod charset;
+pub mod date_encoding;
+pub mod duration_format;
 pub mod field_config;
+pub mod text_transform;
+pub mod validation;
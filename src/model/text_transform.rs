@@ -0,0 +1,88 @@
+use std::borrow::Cow;
+
+use super::field_config::FieldConfig;
+
+/// Case conversion of text, selected with `#[fixed_width(case = "upper")]` or `"lower"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextCase {
+    Upper,
+    Lower,
+}
+
+// base letters of U+00C0..=U+00FF and U+0100..=U+017F, '?' where the letter needs more than one char
+const LATIN_1: &[u8; 64] = b"AAAAAA?CEEEEIIIIDNOOOOO?OUUUUY??aaaaaa?ceeeeiiiidnooooo?ouuuuy?y";
+const LATIN_EXTENDED_A: &[u8; 128] = b"AaAaAaCcCcCcCcDdDdEeEeEeEeEeGgGgGgGgHhHhIiIiIiIiIi??JjKkkLlLlLlLlLlNnNnNnnNnOoOoOo??RrRrRrSsSsSsSsTtTtTtUuUuUuUuUuUuWwYyYZzZzZzs";
+
+/// Replaces accented and special characters with their closest ASCII, e.g. "Nicolò" with "Nicolo"
+/// and "ß" with "ss". Characters with no ASCII counterpart become '?'.
+pub fn transliterate(value: &str) -> Cow<'_, str> {
+    if value.is_ascii() {
+        return Cow::Borrowed(value);
+    }
+
+    let mut res = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            c if c.is_ascii() => res.push(c),
+            'Æ' => res.push_str("AE"),
+            'æ' => res.push_str("ae"),
+            'Œ' => res.push_str("OE"),
+            'œ' => res.push_str("oe"),
+            'Ĳ' => res.push_str("IJ"),
+            'ĳ' => res.push_str("ij"),
+            'ß' => res.push_str("ss"),
+            'Þ' => res.push_str("TH"),
+            'þ' => res.push_str("th"),
+            '×' => res.push('x'),
+            '÷' => res.push('/'),
+            '\u{a0}' => res.push(' '),
+            '‘' | '’' | '‚' | '′' => res.push('\''),
+            '“' | '”' | '„' | '«' | '»' | '″' => res.push('"'),
+            '‐' | '‑' | '‒' | '–' | '—' => res.push('-'),
+            '…' => res.push_str("..."),
+            '€' => res.push_str("EUR"),
+            // combining accents of decomposed text, the letter before them is already there
+            '\u{300}'..='\u{36f}' => {}
+            '\u{c0}'..='\u{ff}' => res.push(LATIN_1[c as usize - 0xc0] as char),
+            '\u{100}'..='\u{17f}' => res.push(LATIN_EXTENDED_A[c as usize - 0x100] as char),
+            _ => res.push('?'),
+        }
+    }
+    Cow::Owned(res)
+}
+
+// transliteration, then case, then whitespace: "ß" becomes "SS" in upper case
+pub(crate) fn transform<'a>(value: &'a str, field_config: &FieldConfig) -> Cow<'a, str> {
+    let mut value = match field_config.transliterate() {
+        true => transliterate(value),
+        false => Cow::Borrowed(value),
+    };
+
+    match field_config.case() {
+        Some(TextCase::Upper) => value = Cow::Owned(value.to_uppercase()),
+        Some(TextCase::Lower) => value = Cow::Owned(value.to_lowercase()),
+        None => {}
+    }
+
+    // runs of whitespace become a single space
+    if field_config.collapse_whitespace() {
+        let mut collapsed = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c.is_whitespace() {
+                true if collapsed.ends_with(' ') => {}
+                true => collapsed.push(' '),
+                false => collapsed.push(c),
+            }
+        }
+        value = Cow::Owned(collapsed);
+    }
+
+    if field_config.trim() {
+        value = match value {
+            Cow::Borrowed(value) => Cow::Borrowed(value.trim()),
+            Cow::Owned(value) => Cow::Owned(value.trim().to_string()),
+        };
+    }
+
+    value
+}
@@ -0,0 +1,130 @@
+use std::fmt::Display;
+
+use bigdecimal::{BigDecimal, FromPrimitive};
+
+use crate::any_value::{AnyNumber, AnyValue, AnyValueTrait};
+
+use super::field_config::FieldConfig;
+
+/// A validation rule broken by a field, e.g. `detail[1].amount: must be at least 0.01, got 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    path: String,
+    message: String,
+}
+
+impl Violation {
+    pub fn new(path: &str, message: String) -> Violation {
+        Violation {
+            path: path.to_string(),
+            message,
+        }
+    }
+
+    /// Path of the field inside the record, e.g. `surname`, `address.zip` or `details[2].qty`.
+    pub fn path(&self) -> &str {
+        self.path.as_ref()
+    }
+
+    pub fn message(&self) -> &str {
+        self.message.as_ref()
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Path of a field of the record at `path`, which is empty for the top level record.
+pub fn field_path(path: &str, field_name: &str) -> String {
+    match path.is_empty() {
+        true => field_name.to_string(),
+        false => format!("{}.{}", path, field_name),
+    }
+}
+
+// numeric value of a field for min and max, text is parsed as a number
+fn number(any_value: &AnyValue, text: &str) -> Option<BigDecimal> {
+    match any_value {
+        AnyValue::Number(n) => match n {
+            AnyNumber::SmallInt(si) => Some(BigDecimal::from(*si)),
+            AnyNumber::Integer(i) => Some(BigDecimal::from(*i)),
+            AnyNumber::BigInteger(bi) => Some(BigDecimal::from(*bi)),
+            AnyNumber::HugeInteger(hi) => Some(BigDecimal::from(*hi)),
+            AnyNumber::UnsignedHugeInteger(uhi) => Some(BigDecimal::from(*uhi)),
+            AnyNumber::Float(f) => BigDecimal::from_f32(*f),
+            AnyNumber::Real(r) => BigDecimal::from_f64(*r),
+            AnyNumber::BigDecimal(bd) => Some(bd.clone()),
+        },
+        AnyValue::String(_) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// checks a value against the rules of its field config, collecting what is broken; a None or a blank
+// value only breaks required, the other rules apply to the text that is written (after the text transforms)
+pub(crate) fn validate<T: AnyValueTrait + ?Sized>(
+    field_config: &FieldConfig,
+    value: &T,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    let mut violation = |message: String| violations.push(Violation::new(path, message));
+
+    let any_value = match value.into_any_value() {
+        Ok(any_value) => any_value,
+        Err(e) => return violation(e.msg().to_string()),
+    };
+    if let AnyValue::Null(_) = any_value {
+        if field_config.required() {
+            violation("is required".to_string());
+        }
+        return;
+    }
+
+    let mut bytes = Vec::new();
+    if let Err(e) = value.write_value(field_config, &mut bytes) {
+        return violation(e.msg().to_string());
+    }
+    crate::transform_text(field_config, &mut bytes, 0);
+    let text = String::from_utf8_lossy(&bytes);
+
+    // blank is as good as None
+    if text.trim().is_empty() {
+        if field_config.required() {
+            violation("is required".to_string());
+        }
+        return;
+    }
+
+    if field_config.min().is_some() || field_config.max().is_some() {
+        match number(&any_value, &text) {
+            Some(number) => {
+                if let Some(min) = field_config.min().filter(|min| number < **min) {
+                    violation(format!("must be at least {}, got {}", min, number));
+                }
+                if let Some(max) = field_config.max().filter(|max| number > **max) {
+                    violation(format!("must be at most {}, got {}", max, number));
+                }
+            }
+            None => violation(format!("'{}' is not a number", text)),
+        }
+    }
+
+    if let Some(pattern) = field_config.pattern() {
+        match field_config.pattern_regex() {
+            Ok(regex) if !regex.is_match(&text) => {
+                violation(format!("'{}' does not match {}", text, pattern))
+            }
+            Ok(_) => {}
+            Err(e) => violation(format!("invalid pattern {}: {}", pattern, e)),
+        }
+    }
+
+    let one_of = field_config.one_of();
+    if !one_of.is_empty() && !one_of.iter().any(|allowed| *allowed == text) {
+        violation(format!("'{}' is not one of {}", text, one_of.join(", ")));
+    }
+}
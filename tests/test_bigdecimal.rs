@@ -30,3 +30,31 @@ fn bigdecimal_test() {
 
     assert_eq!("     2345+".to_string(), s);
 }
+
+#[test]
+fn bigdecimal_decimals() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 5, decimals = 2, pad = "0")]
+        negative: BigDecimal,
+        #[fixed_width(size = 5, decimals = 2, pad = "0")]
+        carry: BigDecimal,
+        #[fixed_width(size = 5, decimals = 3, pad = "0")]
+        zero_fraction: BigDecimal,
+        #[fixed_width(size = 26, decimals = 3, pad = "0")]
+        above_i64: BigDecimal,
+    }
+
+    // decimals counts the sign, so 2 decimals keep one digit after the point
+    let t = Test {
+        negative: "-1.25".parse().unwrap(),
+        carry: "1.96".parse().unwrap(),
+        zero_fraction: "5.0".parse().unwrap(),
+        above_i64: "123456789012345678901.5".parse().unwrap(),
+    };
+
+    assert_eq!(
+        "0013-0020+0500+0012345678901234567890150+",
+        t.to_fixed_width_string().unwrap()
+    );
+}
@@ -0,0 +1,61 @@
+use fixed_width::{model::field_config::FieldConfig, FixedWidth};
+
+// cargo test --test test_bool
+
+#[test]
+fn bool_representations() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(true = "S", false = "N")]
+    struct Test {
+        #[fixed_width(size = 1)]
+        italian: bool,
+        #[fixed_width(size = 1, true = "Y")]
+        english: bool,
+        #[fixed_width(size = 1, true = "X", false = "")]
+        flag: bool,
+        #[fixed_width(size = 1)]
+        missing: Option<bool>,
+        #[fixed_width(size = 1)]
+        present: Option<bool>,
+    }
+
+    let t = Test {
+        italian: true,
+        english: true,
+        flag: false,
+        missing: None,
+        present: Some(false),
+    };
+
+    assert_eq!("SY  N", t.to_fixed_width_string().unwrap());
+}
+
+#[test]
+fn bool_default_representation() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 1)]
+        yes: bool,
+        #[fixed_width(size = 1)]
+        no: bool,
+    }
+
+    let t = Test {
+        yes: true,
+        no: false,
+    };
+
+    assert_eq!("10", t.to_fixed_width_string().unwrap());
+}
+
+#[test]
+fn bool_parse() {
+    let config = FieldConfig::new("flag", 3, b' ', false, 0, "", "", "").with_bool("X", "");
+
+    assert!(config.parse_bool("x  ").unwrap());
+    assert!(!config.parse_bool("   ").unwrap());
+    assert_eq!(
+        "Unrecognised value 'Y  ' for bool field 'flag', expected 'X' or ''",
+        config.parse_bool("Y  ").unwrap_err().msg()
+    );
+}
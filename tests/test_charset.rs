@@ -0,0 +1,98 @@
+use fixed_width::{
+    model::{charset::Charset, field_config::FieldConfig},
+    FixedWidth,
+};
+
+// cargo test --test test_charset
+
+#[derive(Debug, FixedWidth)]
+#[fixed_width(number(charset = "numeric", pad = "0"))]
+struct Test {
+    #[fixed_width(size = 6, charset = "alpha", pad_left = false)]
+    name: String,
+    #[fixed_width(size = 5)]
+    amount: i32,
+    #[fixed_width(size = 6, charset = "[A-Z0-9 ]", case = "upper", pad_left = false)]
+    code: String,
+    #[fixed_width(size = 4, charset = "[a-c\\-]", pad_left = false)]
+    flags: String,
+}
+
+#[test]
+fn charset_write() {
+    let t = Test {
+        name: "Luca".to_string(),
+        amount: 150,
+        code: "ab 12".to_string(),
+        flags: "a-c".to_string(),
+    };
+
+    assert_eq!("Luca  00150AB 12 a-c ", t.to_fixed_width_string().unwrap());
+}
+
+#[test]
+fn charset_write_invalid() {
+    let t = Test {
+        name: "Luca".to_string(),
+        amount: -15,
+        code: "ab_12".to_string(),
+        flags: String::new(),
+    };
+
+    // the position counts the padding before the value
+    assert_eq!(
+        "Invalid character '-' at position 3 of field 'amount', expected numeric",
+        t.to_fixed_width_string().unwrap_err().msg()
+    );
+
+    let t = Test { amount: 15, ..t };
+    assert_eq!(
+        "Invalid character '_' at position 3 of field 'code', expected [A-Z0-9 ]",
+        t.to_fixed_width_string().unwrap_err().msg()
+    );
+}
+
+#[test]
+fn charset_read() {
+    let config = FieldConfig::new("code", 6, b' ', true, 0, "", "", "")
+        .with_charset(Charset::Set(vec![('A', 'Z'), ('0', '9')]));
+
+    assert!(config.check_charset("  AB12").is_ok());
+    assert_eq!(
+        "Invalid character 'b' at position 4 of field 'code', expected [A-Z0-9]",
+        config.check_charset("  Ab12").unwrap_err().msg()
+    );
+}
+
+#[test]
+fn charset_skips_records() {
+    #[derive(Debug, FixedWidth)]
+    struct Detail {
+        #[fixed_width(size = 4, pad_left = false)]
+        code: String,
+    }
+
+    // the records are written by their own fields, neither checked nor upper-cased by the parent
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(charset = "numeric", case = "upper", pad = "0")]
+    struct Parent {
+        #[fixed_width(size = 3)]
+        id: u32,
+        #[fixed_width(size = 8, pad_left = false)]
+        details: Vec<Detail>,
+        #[fixed_width(size = 4)]
+        last: Option<Detail>,
+    }
+
+    let p = Parent {
+        id: 7,
+        details: vec![Detail {
+            code: "ab".to_string(),
+        }],
+        last: Some(Detail {
+            code: "c".to_string(),
+        }),
+    };
+
+    assert_eq!("007ab  0000c   ", p.to_fixed_width_string().unwrap());
+}
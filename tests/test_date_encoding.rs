@@ -0,0 +1,118 @@
+use fixed_width::{
+    model::{date_encoding::DateEncoding, field_config::FieldConfig},
+    FixedWidth,
+};
+use time::macros::{date, datetime};
+
+// cargo test --test test_date_encoding
+
+#[test]
+fn date_encodings() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(date(pad = "0"))]
+    struct Test {
+        #[fixed_width(size = 7, date_encoding = "ordinal")]
+        ordinal: time::Date,
+        #[fixed_width(size = 5, date_encoding = "short_ordinal")]
+        short_ordinal: chrono::NaiveDate,
+        #[fixed_width(size = 6, date_encoding = "short_year")]
+        short_year: time::Date,
+        #[fixed_width(size = 6, date_encoding = "epoch_days")]
+        epoch_days: time::Date,
+        #[fixed_width(size = 6, date_encoding = "epoch_days", epoch = "1900-01-01")]
+        since_1900: Option<time::Date>,
+        #[fixed_width(
+            size = 11,
+            date_encoding = "short_ordinal",
+            time_format = "[hour][minute][second]"
+        )]
+        date_time: time::PrimitiveDateTime,
+        #[fixed_width(size = 11, date_encoding = "ordinal", time_format = "[hour][minute]")]
+        offset_date_time: time::OffsetDateTime,
+        #[fixed_width(size = 10, date_encoding = "short_year", time_format = "%H%M")]
+        utc_date_time: chrono::DateTime<chrono::Utc>,
+    }
+
+    let t = Test {
+        ordinal: date!(2023 - 02 - 01),
+        short_ordinal: chrono::NaiveDate::from_ymd_opt(1999, 12, 31).unwrap(),
+        short_year: date!(2049 - 07 - 04),
+        epoch_days: date!(1970 - 01 - 11),
+        since_1900: Some(date!(1900 - 02 - 01)),
+        date_time: datetime!(2023-02-01 08:05:09),
+        offset_date_time: datetime!(2023-02-01 08:05 UTC),
+        utc_date_time: chrono::DateTime::from_timestamp(1_675_238_700, 0).unwrap(),
+    };
+
+    assert_eq!(
+        "20230329936549070400001000003123032080509202303208052302010805",
+        t.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn date_encoding_parse() {
+    let config = |encoding| {
+        FieldConfig::new("date", 6, b' ', true, 0, "", "", "").with_date_encoding(encoding)
+    };
+
+    assert_eq!(
+        date!(1950 - 01 - 31),
+        config(DateEncoding::ShortYear)
+            .parse_date("500131")
+            .unwrap()
+    );
+    assert_eq!(
+        date!(2049 - 01 - 31),
+        config(DateEncoding::ShortYear)
+            .parse_date("490131")
+            .unwrap()
+    );
+    assert_eq!(
+        date!(1980 - 01 - 31),
+        config(DateEncoding::ShortYear)
+            .with_pivot(80)
+            .parse_date("800131")
+            .unwrap()
+    );
+    assert_eq!(
+        date!(2023 - 02 - 01),
+        config(DateEncoding::Ordinal).parse_date("2023032").unwrap()
+    );
+    assert_eq!(
+        chrono::NaiveDate::from_ymd_opt(1970, 1, 11).unwrap(),
+        config(DateEncoding::EpochDays)
+            .parse_naive_date("    10")
+            .unwrap()
+    );
+    assert_eq!(
+        chrono::NaiveDate::from_ymd_opt(2023, 9, 14).unwrap(),
+        FieldConfig::new("date", 10, b' ', false, 0, "%d%m%Y", "", "")
+            .parse_naive_date("14092023  ")
+            .unwrap()
+    );
+    assert_eq!(
+        "Invalid date '991332' for field 'date'",
+        config(DateEncoding::ShortYear)
+            .parse_date("991332")
+            .unwrap_err()
+            .msg()
+    );
+}
+
+#[test]
+fn date_encoding_outside_pivot() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 6, date_encoding = "short_year")]
+        date: time::Date,
+    }
+
+    let t = Test {
+        date: date!(2075 - 01 - 01),
+    };
+    assert_eq!(
+        "Date 2075-01-01 is outside the two-digit years of field 'date' (pivot 50)",
+        t.to_fixed_width_string().unwrap_err().msg()
+    );
+}
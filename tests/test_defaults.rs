@@ -0,0 +1,125 @@
+use bigdecimal::{BigDecimal, FromPrimitive};
+use fixed_width::FixedWidth;
+
+// cargo test --test test_defaults
+
+#[test]
+fn struct_defaults() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(pad = "*", pad_left = false, date_format = "[day][month][year]")]
+    struct Test {
+        #[fixed_width(size = 10)]
+        name: String,
+        #[fixed_width(size = 10)]
+        date: time::Date,
+        #[fixed_width(size = 5, pad = "0", pad_left = true)]
+        age: u32,
+    }
+
+    let t = Test {
+        name: "pippo".to_string(),
+        date: time::macros::date!(2023 - 09 - 11),
+        age: 25,
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("pippo*****11092023**00025", s);
+}
+
+#[test]
+fn type_defaults() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(
+        text(pad = " ", pad_left = false),
+        number(pad = "0", pad_left = true, decimals = 3)
+    )]
+    struct Test {
+        #[fixed_width(size = 10)]
+        name: Option<String>,
+        #[fixed_width(size = 5)]
+        age: u32,
+        #[fixed_width(size = 8)]
+        amount: BigDecimal,
+        #[fixed_width(size = 5, pad = "_")]
+        code: String,
+    }
+
+    let t = Test {
+        name: Some("pippo".to_string()),
+        age: 25,
+        amount: BigDecimal::from_f64(23.45).unwrap(),
+        code: "AB".to_string(),
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("pippo     000250002345+AB___", s);
+}
+
+#[test]
+fn type_defaults_over_struct_defaults() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(pad = "-", number(pad = "0"))]
+    struct Test {
+        #[fixed_width(size = 6)]
+        name: &'static str,
+        #[fixed_width(size = 4)]
+        age: i64,
+    }
+
+    let t = Test {
+        name: "pippo",
+        age: 7,
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("-pippo0007", s);
+}
+
+#[test]
+fn align_by_type() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(align_by_type)]
+    struct Test {
+        #[fixed_width(size = 10)]
+        name: String,
+        #[fixed_width(size = 5)]
+        age: Option<u32>,
+        #[fixed_width(size = 10)]
+        date: time::Date,
+        #[fixed_width(size = 3)]
+        active: bool,
+        #[fixed_width(size = 5, pad_left = true)]
+        code: String,
+    }
+
+    let t = Test {
+        name: "pippo".to_string(),
+        age: Some(25),
+        date: time::macros::date!(2023 - 09 - 11),
+        active: true,
+        code: "AB".to_string(),
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("pippo     0002520230911  1     AB", s);
+}
+
+#[test]
+fn align_by_type_struct_defaults_win() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(align_by_type, pad = "*", text(pad_left = true))]
+    struct Test {
+        #[fixed_width(size = 8)]
+        name: String,
+        #[fixed_width(size = 4)]
+        age: u32,
+    }
+
+    let t = Test {
+        name: "pippo".to_string(),
+        age: 25,
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("***pippo**25", s);
+}
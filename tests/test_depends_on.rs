@@ -0,0 +1,66 @@
+use fixed_width::FixedWidth;
+
+// cargo test --test test_depends_on
+
+#[derive(Debug, FixedWidth)]
+struct Installment {
+    #[fixed_width(size = 3, pad = "0")]
+    number: u32,
+    #[fixed_width(size = 6, pad = "0")]
+    amount: u32,
+}
+
+#[derive(Debug, FixedWidth)]
+struct Loan {
+    #[fixed_width(size = 5, pad_left = false)]
+    id: String,
+    #[fixed_width(size = 2, pad = "0")]
+    installment_count: u32,
+    #[fixed_width(depends_on = "installment_count", occurs = 3)]
+    installments: Vec<Installment>,
+}
+
+fn installment(number: u32, amount: u32) -> Installment {
+    Installment { number, amount }
+}
+
+#[test]
+fn depends_on_fills_count() {
+    let loan = Loan {
+        id: "L1".to_string(),
+        installment_count: 0,
+        installments: vec![installment(1, 500), installment(2, 750)],
+    };
+
+    assert_eq!(7, Loan::RECORD_LEN);
+    assert_eq!(
+        "L1   02001000500002000750",
+        loan.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn depends_on_empty_group() {
+    let loan = Loan {
+        id: "L2".to_string(),
+        installment_count: 9,
+        installments: Vec::new(),
+    };
+
+    assert_eq!("L2   00", loan.to_fixed_width_string().unwrap());
+}
+
+#[test]
+fn depends_on_above_occurs() {
+    let loan = Loan {
+        id: "L3".to_string(),
+        installment_count: 0,
+        installments: (1..=4).map(|i| installment(i, 100)).collect(),
+    };
+
+    let err = loan.to_fixed_width_string().unwrap_err();
+    assert_eq!(
+        "Expected at most 3 records, got 4 instead in field 'installments'",
+        err.msg()
+    );
+}
@@ -0,0 +1,67 @@
+use fixed_width::{
+    model::{duration_format::DurationFormat, field_config::FieldConfig},
+    FixedWidth,
+};
+
+// cargo test --test test_duration
+
+#[test]
+fn duration_formats() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(align_by_type)]
+    struct Test {
+        #[fixed_width(size = 6)]
+        seconds: std::time::Duration,
+        #[fixed_width(size = 6, duration_format = "minutes", decimals = 2)]
+        minutes: time::Duration,
+        #[fixed_width(size = 8, duration_format = "hhmmss")]
+        elapsed: chrono::TimeDelta,
+        #[fixed_width(size = 4, duration_format = "hhmm")]
+        missing: Option<time::Duration>,
+    }
+
+    let t = Test {
+        seconds: std::time::Duration::from_millis(90_500),
+        minutes: time::Duration::seconds(90),
+        elapsed: chrono::TimeDelta::seconds(30 * 3600 + 5 * 60 + 9),
+        missing: None,
+    };
+
+    assert_eq!(
+        "000091000150003005090000",
+        t.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn duration_parse() {
+    let config = |format| {
+        FieldConfig::new("duration", 8, b'0', true, 2, "", "", "").with_duration_format(format)
+    };
+
+    assert_eq!(
+        time::Duration::seconds(90),
+        config(DurationFormat::Minutes)
+            .parse_duration("00000150")
+            .unwrap()
+    );
+    assert_eq!(
+        time::Duration::seconds(30 * 3600 + 5 * 60 + 9),
+        config(DurationFormat::HhMmSs)
+            .parse_duration("00300509")
+            .unwrap()
+    );
+    assert_eq!(
+        time::Duration::minutes(-75),
+        config(DurationFormat::HhMm)
+            .parse_duration("-0115")
+            .unwrap()
+    );
+    assert_eq!(
+        "Invalid duration '0061' for field 'duration'",
+        config(DurationFormat::HhMm)
+            .parse_duration("0061")
+            .unwrap_err()
+            .msg()
+    );
+}
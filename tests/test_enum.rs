@@ -56,3 +56,64 @@ fn enum_test_impl() {
     let s: String = t.to_fixed_width_string().unwrap();
     assert_eq!("   Maschio   Femmina".to_string(), s);
 }
+
+#[test]
+fn enum_test_keys() {
+    #[derive(Debug, PartialEq, FixedWidthEnum)]
+    #[fixed_width(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Payment {
+        #[fixed_width(key = "01")]
+        Cash,
+        BankTransfer,
+        #[fixed_width(other)]
+        Other(String),
+    }
+
+    #[derive(FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 15, pad_left = false)]
+        payment: Payment,
+    }
+
+    let keys: Vec<String> = [
+        Payment::Cash,
+        Payment::BankTransfer,
+        Payment::Other("99".to_string()),
+    ]
+    .into_iter()
+    .map(|payment| Test { payment }.to_fixed_width_string().unwrap())
+    .collect();
+    assert_eq!(
+        vec!["01             ", "BANK_TRANSFER  ", "99             "],
+        keys
+    );
+
+    assert_eq!(Payment::Cash, Payment::from_key("01").unwrap());
+    assert_eq!(
+        Payment::BankTransfer,
+        Payment::from_key("BANK_TRANSFER  ").unwrap()
+    );
+    assert_eq!(
+        Payment::Other("XX".to_string()),
+        Payment::from_key("XX").unwrap()
+    );
+}
+
+#[test]
+fn enum_test_discriminant_keys() {
+    #[derive(Debug, PartialEq, FixedWidthEnum)]
+    #[fixed_width(discriminant_keys)]
+    enum Status {
+        Active = 1,
+        Suspended,
+        Closed = 9,
+    }
+
+    assert_eq!("1", Status::Active.key());
+    assert_eq!("2", Status::Suspended.key());
+    assert_eq!("9", Status::Closed.key());
+    assert_eq!(Status::Suspended, Status::from_key("2").unwrap());
+
+    let err = Status::from_key("3").unwrap_err();
+    assert_eq!("Unknown key '3' for Status", err.msg());
+}
@@ -0,0 +1,105 @@
+use fixed_width::{any_value::AnyValueTrait, FixedWidth, FixedWidthLayout};
+
+// cargo test --test test_generics
+
+#[test]
+fn generic_struct() {
+    #[derive(FixedWidth)]
+    struct Row<T: AnyValueTrait> {
+        #[fixed_width(size = 5)]
+        code: String,
+        #[fixed_width(size = 5, pad = "0")]
+        value: T,
+    }
+
+    let r = Row {
+        code: "A".to_string(),
+        value: 42u32,
+    };
+    assert_eq!("    A00042", r.to_fixed_width_string().unwrap());
+
+    let r = Row {
+        code: "B".to_string(),
+        value: "x".to_string(),
+    };
+    assert_eq!("    B0000x", r.to_fixed_width_string().unwrap());
+    assert_eq!(10, Row::<u32>::RECORD_LEN);
+}
+
+#[test]
+fn generic_nested_struct() {
+    #[derive(FixedWidth)]
+    struct Item {
+        #[fixed_width(size = 3, pad = "0")]
+        qty: u32,
+    }
+
+    #[derive(FixedWidth)]
+    struct Row<T: FixedWidthLayout> {
+        #[fixed_width(nested)]
+        head: Option<T>,
+        #[fixed_width(occurs = 2)]
+        items: Vec<T>,
+    }
+
+    let r = Row {
+        head: Some(Item { qty: 1 }),
+        items: vec![Item { qty: 2 }],
+    };
+    assert_eq!("001002000", r.to_fixed_width_string().unwrap());
+    assert_eq!(9, Row::<Item>::RECORD_LEN);
+
+    // every instantiation has its own sizes
+    #[derive(FixedWidth)]
+    struct Code {
+        #[fixed_width(size = 5, pad_left = false)]
+        code: String,
+    }
+
+    let r = Row {
+        head: Some(Code {
+            code: "A1".to_string(),
+        }),
+        items: vec![Code {
+            code: "B2".to_string(),
+        }],
+    };
+    assert_eq!("A1   B2        ", r.to_fixed_width_string().unwrap());
+    assert_eq!(15, Row::<Code>::RECORD_LEN);
+}
+
+#[test]
+fn lifetime_struct() {
+    #[derive(FixedWidth)]
+    struct Row<'a> {
+        #[fixed_width(size = 10, pad_left = false)]
+        name: &'a str,
+    }
+
+    let name = String::from("pippo");
+    let r = Row { name: &name };
+    assert_eq!("pippo     ", r.to_fixed_width_string().unwrap());
+}
+
+#[test]
+fn tuple_struct() {
+    #[derive(FixedWidth)]
+    struct Code(#[fixed_width(size = 3)] String);
+
+    #[derive(FixedWidth)]
+    struct Pair(
+        #[fixed_width(size = 3, pad = "0")] u32,
+        #[fixed_width(size = 5, pad_left = false)] String,
+    );
+
+    assert_eq!(
+        "  A",
+        Code("A".to_string()).to_fixed_width_string().unwrap()
+    );
+    assert_eq!(
+        "007pippo",
+        Pair(7, "pippo".to_string())
+            .to_fixed_width_string()
+            .unwrap()
+    );
+}
@@ -0,0 +1,70 @@
+use fixed_width::{FixedWidth, FromFixedWidth};
+
+// cargo test --test test_literal
+
+#[test]
+fn constant_and_filler() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(constant(1, "A"), constant(2, "V2"), filler(14, 20))]
+    struct Test {
+        #[fixed_width(size = 10, pad_left = false)]
+        name: String,
+    }
+
+    let t = Test {
+        name: "pippo".to_string(),
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!(20, Test::RECORD_LEN);
+    assert_eq!("AV2pippo            ", s);
+}
+
+#[test]
+fn filler_between_positioned_fields() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(filler(4, 6, "0"))]
+    struct Test {
+        #[fixed_width(start = 1, end = 3)]
+        code: String,
+        #[fixed_width(start = 7, end = 9)]
+        name: String,
+    }
+
+    let t = Test {
+        code: "A".to_string(),
+        name: "B".to_string(),
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("  A000  B", s);
+}
+
+#[test]
+fn literals_checked_on_read() {
+    #[derive(Debug, PartialEq, FixedWidth)]
+    #[fixed_width(constant(1, "A"), constant(2, "V2"), filler(14, 20), parse)]
+    struct Test {
+        #[fixed_width(size = 10, pad_left = false)]
+        name: String,
+    }
+
+    assert_eq!(
+        Test {
+            name: "pippo".to_string()
+        },
+        Test::from_fixed_width_str("AV2pippo            ").unwrap()
+    );
+    assert_eq!(
+        "Expected 'V2' at column 2, got 'V3' instead",
+        Test::from_fixed_width_str("AV3pippo            ")
+            .unwrap_err()
+            .msg()
+    );
+    assert_eq!(
+        "Expected '       ' at column 14, got '     xx' instead",
+        Test::from_fixed_width_str("AV2pippo          xx")
+            .unwrap_err()
+            .msg()
+    );
+}
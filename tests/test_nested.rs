@@ -0,0 +1,83 @@
+use fixed_width::FixedWidth;
+
+// cargo test --test test_nested
+
+#[derive(Debug, FixedWidth)]
+#[fixed_width(pad_left = false)]
+struct Address {
+    #[fixed_width(size = 10)]
+    street: String,
+    #[fixed_width(size = 5, pad = "0", pad_left = true)]
+    zip: u32,
+}
+
+#[derive(Debug, FixedWidth)]
+#[fixed_width(pad_left = false, record_len = 36)]
+struct Customer {
+    #[fixed_width(size = 6)]
+    name: String,
+    #[fixed_width(nested)]
+    address: Address,
+    #[fixed_width(nested, size = 15)]
+    billing: Option<Address>,
+}
+
+#[test]
+fn nested_inline() {
+    let customer = Customer {
+        name: "pippo".to_string(),
+        address: Address {
+            street: "via roma".to_string(),
+            zip: 123,
+        },
+        billing: Some(Address {
+            street: "via po".to_string(),
+            zip: 10100,
+        }),
+    };
+
+    assert_eq!(36, Customer::RECORD_LEN);
+    assert_eq!(
+        "pippo via roma  00123via po    10100",
+        customer.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn nested_none_is_blank() {
+    let customer = Customer {
+        name: "pluto".to_string(),
+        address: Address {
+            street: "via roma".to_string(),
+            zip: 1,
+        },
+        billing: None,
+    };
+
+    assert_eq!(
+        "pluto via roma  00001          00000",
+        customer.to_fixed_width_string().unwrap()
+    );
+
+    let mut blank = Vec::new();
+    Address::extend_blank_into(&mut blank).unwrap();
+    assert_eq!(b"          00000".to_vec(), blank);
+}
+
+#[test]
+fn nested_too_long() {
+    #[derive(Debug, FixedWidth)]
+    struct Short {
+        #[fixed_width(nested, size = 10)]
+        address: Address,
+    }
+
+    let short = Short {
+        address: Address {
+            street: "via roma".to_string(),
+            zip: 1,
+        },
+    };
+
+    assert!(short.to_fixed_width_string().is_err());
+}
@@ -0,0 +1,65 @@
+use bigdecimal::BigDecimal;
+use fixed_width::{model::field_config::FieldConfig, FixedWidth};
+
+// cargo test --test test_null
+
+#[test]
+fn null_representations() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(pad_left = false, date(null_fill = "0"))]
+    struct Test {
+        #[fixed_width(size = 8)]
+        date: Option<time::Date>,
+        #[fixed_width(size = 6, pad = "0", pad_left = true, null_fill = "0")]
+        amount: Option<BigDecimal>,
+        #[fixed_width(size = 5, null = "N/A")]
+        code: Option<String>,
+        #[fixed_width(size = 5)]
+        note: Option<String>,
+    }
+
+    let t = Test {
+        date: None,
+        amount: None,
+        code: None,
+        note: None,
+    };
+
+    assert_eq!(
+        "00000000000000N/A       ",
+        t.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn null_container_default() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(null = "?", pad_left = false)]
+    struct Test {
+        #[fixed_width(size = 3)]
+        code: Option<u32>,
+        #[fixed_width(size = 3)]
+        name: Option<&'static str>,
+    }
+
+    let t = Test {
+        code: Some(7),
+        name: None,
+    };
+
+    assert_eq!("7  ?  ", t.to_fixed_width_string().unwrap());
+}
+
+#[test]
+fn null_read() {
+    let zeros = FieldConfig::new("date", 8, b' ', true, 0, "", "", "").with_null_fill(b'0');
+    assert!(zeros.is_null("00000000"));
+    assert!(!zeros.is_null("20230911"));
+
+    let sentinel = FieldConfig::new("code", 5, b' ', false, 0, "", "", "").with_null("N/A");
+    assert!(sentinel.is_null("N/A  "));
+    assert!(!sentinel.is_null("     "));
+
+    let blank = FieldConfig::new("name", 5, b' ', false, 0, "", "", "");
+    assert!(blank.is_null("     "));
+}
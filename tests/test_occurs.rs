@@ -0,0 +1,78 @@
+use fixed_width::FixedWidth;
+
+// cargo test --test test_occurs
+
+#[derive(Debug, FixedWidth)]
+struct Detail {
+    #[fixed_width(size = 8, pad_left = false)]
+    code: String,
+    #[fixed_width(size = 4, pad = "0")]
+    qty: u32,
+}
+
+#[derive(Debug, FixedWidth)]
+struct Order {
+    #[fixed_width(size = 5, pad_left = false)]
+    id: String,
+    #[fixed_width(occurs = 3)]
+    details: Vec<Detail>,
+}
+
+#[derive(Debug, FixedWidth)]
+struct Pair {
+    #[fixed_width(occurs = 2)]
+    details: [Detail; 2],
+}
+
+fn detail(code: &str, qty: u32) -> Detail {
+    Detail {
+        code: code.to_string(),
+        qty,
+    }
+}
+
+#[test]
+fn occurs_fills_missing_slots() {
+    let order = Order {
+        id: "A1".to_string(),
+        details: vec![detail("pen", 2)],
+    };
+
+    assert_eq!(5 + 3 * 12, Order::RECORD_LEN);
+    assert_eq!(
+        "A1   pen     0002        0000        0000",
+        order.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn occurs_too_many_records() {
+    let order = Order {
+        id: "A1".to_string(),
+        details: vec![
+            detail("a", 1),
+            detail("b", 2),
+            detail("c", 3),
+            detail("d", 4),
+        ],
+    };
+
+    let err = order.to_fixed_width_string().unwrap_err();
+    assert_eq!(
+        "Expected at most 3 records, got 4 instead in field 'details'",
+        err.msg()
+    );
+}
+
+#[test]
+fn occurs_array() {
+    let pair = Pair {
+        details: [detail("pen", 2), detail("ink", 10)],
+    };
+
+    assert_eq!(24, Pair::RECORD_LEN);
+    assert_eq!(
+        "pen     0002ink     0010",
+        pair.to_fixed_width_string().unwrap()
+    );
+}
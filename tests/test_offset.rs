@@ -0,0 +1,62 @@
+use chrono::TimeZone;
+use fixed_width::FixedWidth;
+use time::macros::datetime;
+
+// cargo test --test test_offset
+
+#[test]
+fn offset_date_time() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(
+        pad_left = false,
+        date_time_format = "[year][month][day][hour][minute][offset_hour sign:mandatory][offset_minute]"
+    )]
+    struct Test {
+        #[fixed_width(size = 17)]
+        original: time::OffsetDateTime,
+        #[fixed_width(size = 17, offset = "+01:00")]
+        rome: time::OffsetDateTime,
+        #[fixed_width(size = 17, offset = "Z")]
+        utc: Option<time::OffsetDateTime>,
+        #[fixed_width(size = 17)]
+        utc_date_time: time::UtcDateTime,
+    }
+
+    let t = Test {
+        original: datetime!(2023-09-11 23:30 -05:00),
+        rome: datetime!(2023-09-11 23:30 -05:00),
+        utc: Some(datetime!(2023-09-11 23:30 -05:00)),
+        utc_date_time: datetime!(2023-09-11 23:30 UTC).to_utc(),
+    };
+
+    assert_eq!(
+        "202309112330-0500202309120530+0100202309120430+0000202309112330+0000",
+        t.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn chrono_date_time() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(date_time_format = "%Y%m%d%H%M%z")]
+    struct Test {
+        #[fixed_width(size = 17)]
+        utc: chrono::DateTime<chrono::Utc>,
+        #[fixed_width(size = 17, offset = "-05:00")]
+        new_york: chrono::DateTime<chrono::Utc>,
+        #[fixed_width(size = 12, date_time_format = "%Y%m%d%H%M")]
+        fixed: Option<chrono::DateTime<chrono::FixedOffset>>,
+    }
+
+    let utc = chrono::Utc.with_ymd_and_hms(2023, 9, 12, 4, 30, 0).unwrap();
+    let t = Test {
+        utc,
+        new_york: utc,
+        fixed: Some(utc.with_timezone(&chrono::FixedOffset::east_opt(7200).unwrap())),
+    };
+
+    assert_eq!(
+        "202309120430+0000202309112330-0500202309120630",
+        t.to_fixed_width_string().unwrap()
+    );
+}
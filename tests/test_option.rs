@@ -1,3 +1,5 @@
+use fixed_width::any_value::{AnyValue, AnyValueTrait};
+use fixed_width::error::FixedWidthError;
 use fixed_width::FixedWidth;
 use fixed_width::FixedWidthEnum;
 
@@ -69,3 +71,61 @@ fn option_enum_test() {
 
     assert_eq!("  M  F".to_string(), s);
 }
+
+#[test]
+fn option_record_test() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 5, pad_left = false)]
+        detail: Option<Detail>,
+        #[fixed_width(size = 5, pad_left = false)]
+        missing: Option<Detail>,
+        #[fixed_width(size = 10, pad_left = false)]
+        details: Option<Vec<Detail>>,
+        #[fixed_width(size = 10, pad = "*")]
+        no_details: Option<Vec<Detail>>,
+    }
+
+    #[derive(Debug, FixedWidth)]
+    struct Detail {
+        #[fixed_width(size = 3)]
+        code: u32,
+    }
+
+    let t = Test {
+        detail: Some(Detail { code: 1 }),
+        missing: None,
+        details: Some(vec![Detail { code: 2 }, Detail { code: 3 }]),
+        no_details: None,
+    };
+    let s: String = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("  1         2  3    **********".to_string(), s);
+}
+
+#[test]
+fn option_custom_type_test() {
+    struct Code(u32);
+
+    impl AnyValueTrait for Code {
+        fn into_any_value(&self) -> Result<AnyValue, FixedWidthError> {
+            Ok(AnyValue::String(format!("C{}", self.0)))
+        }
+    }
+
+    #[derive(FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 4)]
+        code: Option<Code>,
+        #[fixed_width(size = 4)]
+        missing: Option<Code>,
+    }
+
+    let t = Test {
+        code: Some(Code(12)),
+        missing: None,
+    };
+    let s: String = t.to_fixed_width_string().unwrap();
+
+    assert_eq!(" C12    ".to_string(), s);
+}
@@ -0,0 +1,152 @@
+use fixed_width::{FixedWidth, FixedWidthEnum, FromFixedWidth};
+
+// cargo test --test test_parse
+
+#[derive(Debug, PartialEq, FixedWidthEnum)]
+enum Kind {
+    #[fixed_width(key = "P")]
+    Private,
+    #[fixed_width(key = "B")]
+    Business,
+}
+
+#[derive(Debug, PartialEq, FixedWidth)]
+#[fixed_width(pad_left = false, parse)]
+struct Address {
+    #[fixed_width(size = 10)]
+    street: String,
+    #[fixed_width(size = 5, pad = "0", pad_left = true)]
+    zip: u32,
+}
+
+#[derive(Debug, PartialEq, FixedWidth)]
+#[fixed_width(pad_left = false, parse)]
+struct Customer {
+    #[fixed_width(size = 6)]
+    name: String,
+    #[fixed_width(size = 1)]
+    kind: Kind,
+    #[fixed_width(size = 4, pad = "0", pad_left = true)]
+    age: Option<u8>,
+    #[fixed_width(nested)]
+    address: Address,
+    #[fixed_width(nested, size = 15)]
+    billing: Option<Address>,
+}
+
+#[derive(Debug, PartialEq, FixedWidth)]
+#[fixed_width(parse)]
+struct Installment {
+    #[fixed_width(size = 3, pad = "0")]
+    number: u32,
+    #[fixed_width(size = 6, pad = "0")]
+    amount: i32,
+}
+
+#[derive(Debug, PartialEq, FixedWidth)]
+#[fixed_width(parse)]
+struct Loan {
+    #[fixed_width(size = 5, pad_left = false)]
+    id: String,
+    #[fixed_width(size = 2, pad = "0")]
+    installment_count: u32,
+    #[fixed_width(depends_on = "installment_count", occurs = 3)]
+    installments: Vec<Installment>,
+}
+
+#[test]
+fn parse_round_trip() {
+    let customer = Customer {
+        name: "pippo".to_string(),
+        kind: Kind::Business,
+        age: Some(42),
+        address: Address {
+            street: "via roma".to_string(),
+            zip: 123,
+        },
+        billing: None,
+    };
+
+    let text = customer.to_fixed_width_string().unwrap();
+    assert_eq!("pippo B0042via roma  00123          00000", text);
+    assert_eq!(customer, Customer::from_fixed_width_str(&text).unwrap());
+}
+
+#[test]
+fn parse_depends_on() {
+    let loan = Loan::from_fixed_width_str("L1   02001000500002000750").unwrap();
+
+    assert_eq!(
+        Loan {
+            id: "L1".to_string(),
+            installment_count: 2,
+            installments: vec![
+                Installment {
+                    number: 1,
+                    amount: 500
+                },
+                Installment {
+                    number: 2,
+                    amount: 750
+                },
+            ],
+        },
+        loan
+    );
+    assert_eq!(
+        Vec::<Installment>::new(),
+        Loan::from_fixed_width_str("L2   00").unwrap().installments
+    );
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        "Expected at most 3 records, got a count of 4 instead in field 'installments'",
+        Loan::from_fixed_width_str("L3   04").unwrap_err().msg()
+    );
+    assert_eq!(
+        "Expected a record of 16 bytes, got 17 instead",
+        Loan::from_fixed_width_str("L4   01001000500 ")
+            .unwrap_err()
+            .msg()
+    );
+    assert!(Loan::from_fixed_width_str("L5   0100100").is_err());
+    assert!(Customer::from_fixed_width_str("pippo X0042via roma  00123               ").is_err());
+}
+
+#[derive(Debug, PartialEq, FixedWidth)]
+#[fixed_width(parse)]
+struct Order {
+    #[fixed_width(size = 5, pad_left = false)]
+    id: String,
+    #[fixed_width(occurs = 3)]
+    installments: Vec<Installment>,
+    #[fixed_width(occurs = 2)]
+    pair: [Installment; 2],
+}
+
+#[test]
+fn parse_occurs() {
+    let order = Order {
+        id: "A1".to_string(),
+        installments: vec![Installment {
+            number: 1,
+            amount: 500,
+        }],
+        pair: [
+            Installment {
+                number: 0,
+                amount: 0,
+            },
+            Installment {
+                number: 2,
+                amount: -7,
+            },
+        ],
+    };
+
+    let text = order.to_fixed_width_string().unwrap();
+    assert_eq!("A1   0010005000000000000000000000000000000020000-7", text);
+    assert_eq!(order, Order::from_fixed_width_str(&text).unwrap());
+}
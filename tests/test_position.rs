@@ -0,0 +1,68 @@
+use fixed_width::FixedWidth;
+
+// cargo test --test test_position
+
+#[test]
+fn position_start_end() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(start = 1, end = 16, pad_left = false)]
+        tax_code: String,
+        #[fixed_width(start = 17, end = 40, pad_left = false)]
+        surname: String,
+        #[fixed_width(start = 41, size = 3, pad = "0")]
+        age: u32,
+    }
+
+    let t = Test {
+        tax_code: "RSSMRA80A01H501U".to_string(),
+        surname: "Rossi".to_string(),
+        age: 43,
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!(43, Test::RECORD_LEN);
+    assert_eq!("RSSMRA80A01H501URossi                   043", s);
+}
+
+#[test]
+fn position_mixed_with_size() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 5)]
+        code: String,
+        #[fixed_width(start = 6, end = 10)]
+        name: String,
+        #[fixed_width(size = 2)]
+        age: u32,
+    }
+
+    let t = Test {
+        code: "A".to_string(),
+        name: "B".to_string(),
+        age: 3,
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("    A    B 3", s);
+}
+
+#[test]
+fn position_gap_filler() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(gap_filler = "*", record_len = 10)]
+    struct Test {
+        #[fixed_width(start = 3, end = 5)]
+        code: String,
+        #[fixed_width(start = 8, end = 10)]
+        name: String,
+    }
+
+    let t = Test {
+        code: "A".to_string(),
+        name: "B".to_string(),
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("**  A**  B", s);
+}
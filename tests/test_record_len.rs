@@ -0,0 +1,45 @@
+use fixed_width::FixedWidth;
+
+// cargo test --test test_record_len
+
+#[test]
+fn record_len_const() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 16)]
+        tax_code: String,
+        #[fixed_width(size = 24)]
+        surname: String,
+        #[fixed_width(size = 3)]
+        age: u32,
+    }
+
+    assert_eq!(43, Test::RECORD_LEN);
+
+    let t = Test {
+        tax_code: "RSSMRA80A01H501U".to_string(),
+        surname: "Rossi".to_string(),
+        age: 43,
+    };
+    let s = t.to_fixed_width_string().unwrap();
+    assert_eq!(Test::RECORD_LEN, s.len());
+}
+
+#[test]
+fn record_len_checked() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(record_len = 20)]
+    struct Test {
+        #[fixed_width(size = 10)]
+        name: String,
+        #[fixed_width(size = 10)]
+        description: String,
+    }
+
+    let t = Test {
+        name: "pippo".to_string(),
+        description: "pluto".to_string(),
+    };
+    let s = t.to_fixed_width_string().unwrap();
+    assert_eq!(20, s.len());
+}
@@ -152,3 +152,53 @@ fn test_multi() {
     let s: String = t.to_fixed_width_string().unwrap();
     assert_eq!("pippo$$$$$202309****", s);
 }
+
+#[test]
+fn date_format_many_records() {
+    #[derive(Debug, FixedWidth)]
+    struct T {
+        #[fixed_width(size = 8)]
+        date: time::Date,
+        #[fixed_width(size = 6, time_format = "[hour][minute][second]")]
+        time: time::Time,
+    }
+
+    let days = [
+        time::macros::date!(2023 - 09 - 11),
+        time::macros::date!(2023 - 09 - 12),
+        time::macros::date!(2023 - 09 - 13),
+    ];
+    let rows: Vec<String> = days
+        .iter()
+        .map(|d| {
+            T {
+                date: *d,
+                time: time::macros::time!(08:05:01),
+            }
+            .to_fixed_width_string()
+            .unwrap()
+        })
+        .collect();
+
+    assert_eq!(
+        vec!["20230911080501", "20230912080501", "20230913080501"],
+        rows
+    );
+}
+
+#[test]
+fn invalid_date_format() {
+    #[derive(Debug, FixedWidth)]
+    struct T {
+        #[fixed_width(size = 8, date_format = "[year][month")]
+        date: time::Date,
+    }
+
+    let t = T {
+        date: time::macros::date!(2023 - 09 - 11),
+    };
+
+    // the parse error is cached with the format and reported for every record
+    assert!(t.to_fixed_width_string().is_err());
+    assert!(t.to_fixed_width_string().is_err());
+}
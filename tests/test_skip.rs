@@ -0,0 +1,88 @@
+use fixed_width::{FixedWidth, FromFixedWidth};
+
+// cargo test --test test_skip
+
+#[test]
+fn skip_field() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 10)]
+        name: String,
+        #[fixed_width(skip)]
+        #[allow(dead_code)]
+        id: u64,
+        #[fixed_width(size = 3)]
+        age: u32,
+    }
+
+    let t = Test {
+        name: "pippo".to_string(),
+        id: 42,
+        age: 25,
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!(13, Test::RECORD_LEN);
+    assert_eq!("     pippo 25", s);
+}
+
+#[test]
+fn computed_field() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 5)]
+        code: String,
+        #[fixed_width(size = 2, pad = "0", compute = "Self::checksum")]
+        checksum: (),
+        #[fixed_width(size = 3, compute = "Self::len")]
+        len: (),
+    }
+
+    impl Test {
+        fn checksum(&self) -> u32 {
+            self.code.bytes().map(u32::from).sum::<u32>() % 100
+        }
+
+        fn len(&self) -> String {
+            self.code.len().to_string()
+        }
+    }
+
+    let t = Test {
+        code: "AB".to_string(),
+        checksum: (),
+        len: (),
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("   AB31  2", s);
+}
+
+#[test]
+fn skip_and_computed_on_read() {
+    #[derive(Debug, PartialEq, FixedWidth)]
+    #[fixed_width(parse)]
+    struct Test {
+        #[fixed_width(size = 5)]
+        code: String,
+        #[fixed_width(skip)]
+        id: u64,
+        #[fixed_width(size = 3, compute = "Self::len")]
+        len: (),
+    }
+
+    impl Test {
+        fn len(&self) -> String {
+            self.code.len().to_string()
+        }
+    }
+
+    assert_eq!(
+        Test {
+            code: "AB".to_string(),
+            id: 0,
+            len: ()
+        },
+        Test::from_fixed_width_str("   AB  2").unwrap()
+    );
+}
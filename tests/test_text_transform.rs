@@ -0,0 +1,65 @@
+use fixed_width::{model::text_transform::transliterate, FixedWidth};
+
+// cargo test --test test_text_transform
+
+#[test]
+fn text_transform_record() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(case = "upper", transliterate, pad_left = false)]
+    struct Test {
+        #[fixed_width(size = 8)]
+        name: String,
+        #[fixed_width(size = 8)]
+        surname: String,
+        #[fixed_width(size = 6, case = "lower")]
+        code: String,
+        #[fixed_width(size = 6, transliterate = false)]
+        city: String,
+    }
+
+    let t = Test {
+        name: "Nicolò".to_string(),
+        surname: "Straße".to_string(),
+        code: "AbC".to_string(),
+        city: "Forlì".to_string(),
+    };
+
+    // "FORLÌ" is 6 bytes in UTF-8
+    assert_eq!(
+        "NICOLO  STRASSE abc   FORLÌ",
+        t.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn text_transform_whitespace() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 5, trim, pad_left = false)]
+        trimmed: String,
+        #[fixed_width(size = 12, collapse_whitespace, trim, pad = "*")]
+        collapsed: &'static str,
+        #[fixed_width(size = 8, pad_left = false)]
+        untouched: String,
+    }
+
+    let t = Test {
+        trimmed: "   abc   ".to_string(),
+        collapsed: " via \t Roma   10 ",
+        untouched: " a  b ".to_string(),
+    };
+
+    assert_eq!(
+        "abc  *via Roma 10 a  b   ",
+        t.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn text_transform_transliterate() {
+    assert_eq!("Nicolo", transliterate("Nicolò"));
+    assert_eq!("ss AE oe Lodz", transliterate("ß Æ œ Łódź"));
+    assert_eq!("\"EUR 5 - caffe\"...", transliterate("“€ 5 – caffè”…"));
+    assert_eq!("Cafe", transliterate("Cafe\u{301}"));
+    assert_eq!("?", transliterate("中"));
+}
@@ -0,0 +1,113 @@
+use std::{borrow::Cow, net::IpAddr, sync::Arc};
+
+use fixed_width::FixedWidth;
+
+// cargo test --test test_types
+
+#[test]
+fn text_types() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(pad_left = false)]
+    struct Test<'a> {
+        #[fixed_width(size = 2)]
+        gender: char,
+        #[fixed_width(size = 2)]
+        missing: Option<char>,
+        #[fixed_width(size = 6)]
+        boxed: Box<str>,
+        #[fixed_width(size = 6)]
+        cow: Cow<'a, str>,
+        #[fixed_width(size = 6)]
+        shared: Arc<str>,
+        #[fixed_width(size = 16)]
+        ip: IpAddr,
+    }
+
+    let t = Test {
+        gender: 'F',
+        missing: None,
+        boxed: "box".into(),
+        cow: Cow::Borrowed("cow"),
+        shared: Arc::from("arc"),
+        ip: "192.168.0.1".parse().unwrap(),
+    };
+
+    assert_eq!(
+        "F   box   cow   arc   192.168.0.1     ",
+        t.to_fixed_width_string().unwrap()
+    );
+}
+
+#[test]
+fn wrapper_types() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(pad = "0")]
+    struct Test<'a> {
+        #[fixed_width(size = 3)]
+        reference: &'a u32,
+        #[fixed_width(size = 3)]
+        boxed: Box<u32>,
+        #[fixed_width(size = 3)]
+        shared: Arc<u32>,
+        #[fixed_width(size = 3)]
+        boxed_option: Box<Option<u32>>,
+        #[fixed_width(size = 3)]
+        option_reference: Option<&'a u32>,
+        #[fixed_width(size = 3)]
+        option_boxed: Option<Box<u32>>,
+    }
+
+    let t = Test {
+        reference: &1,
+        boxed: Box::new(2),
+        shared: Arc::new(3),
+        boxed_option: Box::new(None),
+        option_reference: Some(&4),
+        option_boxed: None,
+    };
+
+    assert_eq!("001002003000004000", t.to_fixed_width_string().unwrap());
+}
+
+#[test]
+fn integer_types() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(pad = "0")]
+    struct Test {
+        #[fixed_width(size = 3)]
+        byte: u8,
+        #[fixed_width(size = 4)]
+        signed_byte: i8,
+        #[fixed_width(size = 5)]
+        word: u16,
+        #[fixed_width(size = 10)]
+        double_word: u32,
+        #[fixed_width(size = 20)]
+        quad_word: u64,
+        #[fixed_width(size = 3)]
+        index: usize,
+        #[fixed_width(size = 3)]
+        offset: isize,
+        #[fixed_width(size = 39)]
+        huge: u128,
+        #[fixed_width(size = 5)]
+        signed_huge: i128,
+    }
+
+    let t = Test {
+        byte: u8::MAX,
+        signed_byte: i8::MIN,
+        word: u16::MAX,
+        double_word: u32::MAX,
+        quad_word: u64::MAX,
+        index: 42,
+        offset: -7,
+        huge: u128::MAX,
+        signed_huge: -1234,
+    };
+
+    assert_eq!(
+        "255-128655354294967295184467440737095516150420-7340282366920938463463374607431768211455-1234",
+        t.to_fixed_width_string().unwrap()
+    );
+}
@@ -0,0 +1,8 @@
+// compile errors reported by the derive macros
+// cargo test --test test_ui, regenerate the expected output with TRYBUILD=overwrite
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
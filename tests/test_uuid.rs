@@ -0,0 +1,33 @@
+#![cfg(feature = "uuid")]
+
+use fixed_width::FixedWidth;
+
+// cargo test --features uuid --test test_uuid
+
+#[test]
+fn uuid_forms() {
+    #[derive(Debug, FixedWidth)]
+    struct Test {
+        #[fixed_width(size = 36)]
+        hyphenated: uuid::Uuid,
+        #[fixed_width(size = 32, uuid_format = "simple")]
+        simple: uuid::Uuid,
+        #[fixed_width(size = 32, uuid_format = "simple")]
+        missing: Option<uuid::Uuid>,
+    }
+
+    let uuid = uuid::Uuid::from_u128(0x67e5504410b1426f9247bb680e5fe0c8);
+    let t = Test {
+        hyphenated: uuid,
+        simple: uuid,
+        missing: None,
+    };
+
+    assert_eq!(
+        format!(
+            "67e55044-10b1-426f-9247-bb680e5fe0c867e5504410b1426f9247bb680e5fe0c8{}",
+            " ".repeat(32)
+        ),
+        t.to_fixed_width_string().unwrap()
+    );
+}
@@ -0,0 +1,106 @@
+use fixed_width::{model::field_config::FieldConfig, FixedWidth};
+
+// cargo test --test test_validation
+
+#[derive(Debug, FixedWidth)]
+struct Address {
+    #[fixed_width(size = 5, pattern = "^\\d{5}$")]
+    zip: String,
+    #[fixed_width(size = 2, one_of("MI", "RM", "TO"))]
+    province: String,
+}
+
+#[derive(Debug, FixedWidth)]
+struct Person {
+    #[fixed_width(size = 10, required)]
+    surname: String,
+    #[fixed_width(size = 4, min = 1900, max = 2100)]
+    year: u32,
+    #[fixed_width(size = 8, decimals = 2, min = 0.01)]
+    amount: f64,
+    #[fixed_width(size = 3, min = -10)]
+    delta: Option<i32>,
+    #[fixed_width(nested)]
+    address: Address,
+    #[fixed_width(occurs = 2)]
+    previous: Vec<Address>,
+}
+
+fn address(zip: &str, province: &str) -> Address {
+    Address {
+        zip: zip.to_string(),
+        province: province.to_string(),
+    }
+}
+
+#[test]
+fn validation_valid() {
+    let person = Person {
+        surname: "Rossi".to_string(),
+        year: 1980,
+        amount: 10.5,
+        delta: None,
+        address: address("20100", "MI"),
+        previous: vec![address("00100", "RM")],
+    };
+
+    assert!(person.validate().is_ok());
+}
+
+#[test]
+fn validation_all_violations() {
+    let person = Person {
+        surname: "  ".to_string(),
+        year: 1850,
+        amount: 0.0,
+        delta: Some(-11),
+        address: address("2010A", "MI"),
+        previous: vec![address("00100", "RM"), address("10100", "NA")],
+    };
+
+    let err = person.validate().unwrap_err();
+    let violations: Vec<String> = err.violations().iter().map(|v| v.to_string()).collect();
+    assert_eq!(
+        vec![
+            "surname: is required",
+            "year: must be at least 1900, got 1850",
+            "amount: must be at least 0.01, got 0",
+            "delta: must be at least -10, got -11",
+            "address.zip: '2010A' does not match ^\\d{5}$",
+            "previous[1].province: 'NA' is not one of MI, RM, TO",
+        ],
+        violations
+    );
+    assert!(err
+        .msg()
+        .starts_with("Validation failed: surname: is required; year:"));
+}
+
+#[test]
+fn validation_on_write() {
+    #[derive(Debug, FixedWidth)]
+    #[fixed_width(validate)]
+    struct Test {
+        #[fixed_width(size = 3, max = 100)]
+        qty: u32,
+    }
+
+    assert_eq!("100", Test { qty: 100 }.to_fixed_width_string().unwrap());
+    assert_eq!(
+        "Validation failed: qty: must be at most 100, got 101",
+        Test { qty: 101 }.to_fixed_width_string().unwrap_err().msg()
+    );
+
+    // a value read back is checked as text
+    let config = FieldConfig::new("year", 4, b' ', true, 0, "", "", "")
+        .with_min(1900.into())
+        .with_required(true);
+    let mut violations = Vec::new();
+    config.validate(&"1899", "year", &mut violations);
+    config.validate(&"", "year", &mut violations);
+    let violations: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+    assert_eq!(
+        vec!["year: must be at least 1900, got 1899", "year: is required",],
+        violations
+    );
+}
@@ -0,0 +1,99 @@
+use fixed_width::{
+    error::FixedWidthError, model::field_config::FieldConfig, FixedWidth, FromFixedWidth,
+};
+
+// cargo test --test test_with
+
+mod fiscal_code {
+    use fixed_width::{error::FixedWidthError, model::field_config::FieldConfig};
+
+    pub fn serialize(value: &impl AsRef<str>, _: &FieldConfig) -> Result<Vec<u8>, FixedWidthError> {
+        let value = value.as_ref();
+        if value.len() != 16 {
+            return Err(FixedWidthError::new(format!(
+                "Invalid fiscal code '{}'",
+                value
+            )));
+        }
+        Ok(value.to_uppercase().into_bytes())
+    }
+
+    pub fn deserialize(value: &str, _: &FieldConfig) -> Result<String, FixedWidthError> {
+        Ok(value.to_lowercase())
+    }
+}
+
+fn days_since_1900(date: &time::Date, _: &FieldConfig) -> Result<Vec<u8>, FixedWidthError> {
+    let days = (*date - time::macros::date!(1900 - 01 - 01)).whole_days();
+    Ok(days.to_string().into_bytes())
+}
+
+fn from_days_since_1900(days: &str, _: &FieldConfig) -> Result<time::Date, FixedWidthError> {
+    let days: i64 = days
+        .parse()
+        .map_err(|_| FixedWidthError::new(format!("Invalid days '{}'", days)))?;
+    Ok(time::macros::date!(1900 - 01 - 01) + time::Duration::days(days))
+}
+
+#[derive(Debug, FixedWidth)]
+struct Test {
+    #[fixed_width(size = 16, with = "fiscal_code")]
+    fiscal_code: String,
+    #[fixed_width(size = 6, pad = "0", serialize_with = "days_since_1900")]
+    date: time::Date,
+}
+
+#[test]
+fn with_module_and_serialize_with() {
+    let t = Test {
+        fiscal_code: "rssmra80a01h501u".to_string(),
+        date: time::macros::date!(2023 - 09 - 11),
+    };
+    let s = t.to_fixed_width_string().unwrap();
+
+    assert_eq!("RSSMRA80A01H501U045178", s);
+}
+
+#[test]
+fn with_error() {
+    let t = Test {
+        fiscal_code: "rssmra".to_string(),
+        date: time::macros::date!(2023 - 09 - 11),
+    };
+    let e = t.to_fixed_width_string().unwrap_err();
+
+    assert_eq!("Invalid fiscal code 'rssmra'", e.msg());
+}
+
+#[test]
+fn with_module_and_deserialize_with() {
+    #[derive(Debug, PartialEq, FixedWidth)]
+    #[fixed_width(parse)]
+    struct Parsed {
+        #[fixed_width(size = 16, with = "fiscal_code")]
+        fiscal_code: String,
+        #[fixed_width(
+            size = 6,
+            pad = "0",
+            serialize_with = "days_since_1900",
+            deserialize_with = "from_days_since_1900"
+        )]
+        date: time::Date,
+    }
+
+    let t = Parsed::from_fixed_width_str("RSSMRA80A01H501U045178").unwrap();
+
+    assert_eq!(
+        Parsed {
+            fiscal_code: "rssmra80a01h501u".to_string(),
+            date: time::macros::date!(2023 - 09 - 11),
+        },
+        t
+    );
+    assert_eq!(
+        "Invalid days '04517x'",
+        Parsed::from_fixed_width_str("RSSMRA80A01H501U04517x")
+            .unwrap_err()
+            .msg()
+    );
+}
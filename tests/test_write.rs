@@ -0,0 +1,73 @@
+use fixed_width::FixedWidth;
+
+// cargo test --test test_write
+
+#[derive(Debug, FixedWidth)]
+struct Row {
+    #[fixed_width(size = 10, pad_left = false)]
+    name: String,
+    #[fixed_width(size = 5, pad = "0")]
+    age: u32,
+}
+
+#[test]
+fn extend_into_reuses_buffer() {
+    let rows = vec![
+        Row {
+            name: "pippo".to_string(),
+            age: 25,
+        },
+        Row {
+            name: "pluto".to_string(),
+            age: 7,
+        },
+    ];
+
+    let mut buf = Vec::new();
+    for row in &rows {
+        row.extend_into(&mut buf).unwrap();
+        buf.push(b'\n');
+    }
+
+    assert_eq!(
+        "pippo     00025\npluto     00007\n",
+        String::from_utf8(buf).unwrap()
+    );
+}
+
+#[test]
+fn write_fixed_width_to_writer() {
+    let mut out = std::io::Cursor::new(Vec::new());
+    for i in 0..3 {
+        let row = Row {
+            name: format!("row{}", i),
+            age: i,
+        };
+        row.write_fixed_width(&mut out).unwrap();
+    }
+
+    assert_eq!(
+        "row0      00000row1      00001row2      00002",
+        String::from_utf8(out.into_inner()).unwrap()
+    );
+}
+
+#[test]
+fn extend_into_error_keeps_buffer() {
+    let mut buf = b"header".to_vec();
+    let row = Row {
+        name: "a name that is too long".to_string(),
+        age: 1,
+    };
+
+    assert!(row.extend_into(&mut buf).is_err());
+    assert_eq!(b"header".to_vec(), buf);
+}
+
+#[test]
+fn pad_large_field() {
+    let bytes = fixed_width::pad(&"pippo", "name", 10_000, b' ', true, 0, "", "", "").unwrap();
+
+    assert_eq!(10_000, bytes.len());
+    assert!(bytes.ends_with(b" pippo"));
+}
@@ -0,0 +1,17 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Detail {
+    #[fixed_width(size = 10)]
+    name: String,
+}
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(size = 2)]
+    count: u32,
+    #[fixed_width(depends_on = "detail_count")]
+    details: Vec<Detail>,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use fixed_width::{error::FixedWidthError, model::field_config::FieldConfig, FixedWidth};
+
+fn write_code(value: &String, _: &FieldConfig) -> Result<Vec<u8>, FixedWidthError> {
+    Ok(value.clone().into_bytes())
+}
+
+fn read_code(value: &str, _: &FieldConfig) -> Result<String, FixedWidthError> {
+    Ok(value.to_string())
+}
+
+#[derive(FixedWidth)]
+#[fixed_width(parse)]
+struct Parsed {
+    #[fixed_width(size = 3, serialize_with = "write_code")]
+    code: String,
+}
+
+#[derive(FixedWidth)]
+struct Written {
+    #[fixed_width(size = 3, serialize_with = "write_code", deserialize_with = "read_code")]
+    code: String,
+}
+
+fn main() {}
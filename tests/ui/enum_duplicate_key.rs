@@ -0,0 +1,11 @@
+use fixed_width::FixedWidthEnum;
+
+#[derive(FixedWidthEnum)]
+#[fixed_width(rename_all = "lowercase")]
+enum Test {
+    #[fixed_width(key = "b")]
+    A,
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use fixed_width::FixedWidthEnum;
+
+#[derive(FixedWidthEnum)]
+struct Test {
+    name: String,
+}
+
+fn main() {}
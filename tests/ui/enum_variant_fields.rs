@@ -0,0 +1,9 @@
+use fixed_width::FixedWidthEnum;
+
+#[derive(FixedWidthEnum)]
+enum Test {
+    A,
+    B(String),
+}
+
+fn main() {}
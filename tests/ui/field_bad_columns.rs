@@ -0,0 +1,13 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(start = 10, end = 5)]
+    name: String,
+    #[fixed_width(end = 20)]
+    surname: String,
+    #[fixed_width(start = 21, end = 30, size = 5)]
+    city: String,
+}
+
+fn main() {}
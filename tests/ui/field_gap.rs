@@ -0,0 +1,11 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(start = 1, end = 10)]
+    name: String,
+    #[fixed_width(start = 12, end = 20)]
+    surname: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(size = 6, date_encoding = "epoch_days", epoch = "1900/01/01")]
+    date: time::Date,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(size = 20, offset = "CET")]
+    date: time::OffsetDateTime,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Test {
+    // chrono offsets stop at 23:59
+    #[fixed_width(size = 20, offset = "+24:00")]
+    date: chrono::DateTime<chrono::FixedOffset>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(size = 5, pattern = "(")]
+    zip: String,
+}
+
+fn main() {}
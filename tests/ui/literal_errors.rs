@@ -0,0 +1,10 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+#[fixed_width(filler(30, 20), constant(5, "A"), filler(40, 41, "é"))]
+struct Test {
+    #[fixed_width(size = 10)]
+    name: String,
+}
+
+fn main() {}
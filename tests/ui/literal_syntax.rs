@@ -0,0 +1,10 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+#[fixed_width(filler(40), constant(1, "A", "B"))]
+struct Test {
+    #[fixed_width(size = 10)]
+    name: String,
+}
+
+fn main() {}
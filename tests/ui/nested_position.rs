@@ -0,0 +1,17 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Address {
+    #[fixed_width(size = 10)]
+    street: String,
+}
+
+#[derive(FixedWidth)]
+struct Customer {
+    #[fixed_width(nested)]
+    address: Address,
+    #[fixed_width(start = 11, size = 5)]
+    name: String,
+}
+
+fn main() {}
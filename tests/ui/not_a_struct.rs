@@ -0,0 +1,8 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+enum Test {
+    A,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Detail {
+    #[fixed_width(size = 4)]
+    code: String,
+}
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(occurs = 3)]
+    details: [Detail; 2],
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+#[fixed_width(pad = "é", number(pad = "ø"))]
+struct Test {
+    #[fixed_width(size = 5)]
+    code: String,
+    #[fixed_width(size = 5)]
+    qty: u32,
+    #[fixed_width(size = 5, pad = "ñ")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+#[fixed_width(parse)]
+struct Item {
+    #[fixed_width(size = 3)]
+    code: String,
+}
+
+#[derive(FixedWidth)]
+#[fixed_width(parse)]
+struct Order {
+    #[fixed_width(depends_on = "count")]
+    items: Vec<Item>,
+    #[fixed_width(size = 2)]
+    count: usize,
+}
+
+fn main() {}
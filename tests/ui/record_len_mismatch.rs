@@ -0,0 +1,10 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+#[fixed_width(record_len = 1900)]
+struct Test {
+    #[fixed_width(size = 16)]
+    tax_code: String,
+}
+
+fn main() {}
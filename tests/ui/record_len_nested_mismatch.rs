@@ -0,0 +1,19 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Address {
+    #[fixed_width(size = 10)]
+    city: String,
+}
+
+// RECORD_LEN is never used, the check fires anyway
+#[derive(FixedWidth)]
+#[fixed_width(record_len = 20)]
+struct Test {
+    #[fixed_width(size = 16)]
+    tax_code: String,
+    #[fixed_width(nested)]
+    address: Address,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(size = 10, with = "a", serialize_with = "b")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use fixed_width::FixedWidth;
+
+#[derive(FixedWidth)]
+struct Detail {
+    #[fixed_width(size = 3)]
+    code: u32,
+}
+
+#[derive(FixedWidth)]
+struct Test {
+    #[fixed_width(nested, required)]
+    detail: Detail,
+}
+
+fn main() {}